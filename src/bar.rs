@@ -0,0 +1,509 @@
+use std::collections::HashSet;
+use yew::prelude::*;
+
+use crate::chart::MARGINS;
+use crate::render::{self, LegendEntry};
+use crate::series;
+use crate::stack::{self, Segment, Stack};
+
+/// One value per category, drawn as a bar in each category's band.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BarSeries {
+    pub name: String,
+    /// Values in the order of the chart's `categories`
+    pub values: Vec<f32>,
+    /// Hex color such as `#FF0000`; picked from `PALETTE` when unset
+    pub color: Option<String>,
+    /// Lower and upper bound of each value, drawn as error bars unless stacked
+    pub bounds: Vec<(f32, f32)>,
+}
+
+impl BarSeries {
+    pub fn new<S: Into<String>>(name: S, values: Vec<f32>) -> Self {
+        Self {
+            name: name.into(),
+            values,
+            color: None,
+            bounds: Vec::new(),
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: Vec<(f32, f32)>) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn with_color<S: Into<String>>(mut self, color: S) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    /// Categories along x, bars growing up
+    Vertical,
+    /// Categories along y, bars growing right
+    Horizontal,
+}
+
+/// The bar under the pointer or clicked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BarEvent {
+    pub category: String,
+    pub series: String,
+    pub value: f32,
+    /// Sum of the visible series in the category
+    pub total: f32,
+    pub bounds: Option<(f32, f32)>,
+}
+
+/// Where each category's band sits along the category axis, laid out like
+/// `charts::ScaleBand` with equal inner and outer padding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bands {
+    start: f32,
+    step: f32,
+    pub bandwidth: f32,
+}
+
+impl Bands {
+    pub fn new(count: usize, length: f32, padding: f32) -> Self {
+        // d3's `n - inner + 2 * outer` with both paddings equal
+        let step = length / (count as f32 + padding).max(1.0);
+        Self {
+            start: step * padding,
+            step,
+            bandwidth: step * (1.0 - padding),
+        }
+    }
+
+    pub fn offset(&self, idx: usize) -> f32 {
+        self.start + self.step * idx as f32
+    }
+}
+
+/// Pixel geometry of the plot area shared by the bar-like charts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub orientation: Orientation,
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub domain: (f32, f32),
+}
+
+impl Frame {
+    /// A domain with no width is widened by one either side.
+    pub fn new(width: isize, height: isize, orientation: Orientation, domain: (f32, f32)) -> Self {
+        let (top, right, bottom, left) = MARGINS;
+        let domain = match domain.0 == domain.1 {
+            true => (domain.0 - 1.0, domain.1 + 1.0),
+            false => domain,
+        };
+        Self {
+            orientation,
+            left: left as f32,
+            top: top as f32,
+            width: (width - left - right) as f32,
+            height: (height - top - bottom) as f32,
+            domain,
+        }
+    }
+
+    /// Length of the category axis.
+    pub fn band_length(&self) -> f32 {
+        match self.orientation {
+            Orientation::Vertical => self.width,
+            Orientation::Horizontal => self.height,
+        }
+    }
+
+    /// Position of a value along the value axis, in svg units.
+    pub fn value_pos(&self, v: f32) -> f32 {
+        let (lo, hi) = self.domain;
+        let t = ((v - lo) / (hi - lo)).max(0.0).min(1.0);
+        match self.orientation {
+            Orientation::Vertical => self.top + self.height * (1.0 - t),
+            Orientation::Horizontal => self.left + self.width * t,
+        }
+    }
+
+    /// The svg point `along` the category axis and at `v` on the value axis.
+    pub fn point(&self, along: f32, v: f32) -> (f32, f32) {
+        match self.orientation {
+            Orientation::Vertical => (self.left + along, self.value_pos(v)),
+            Orientation::Horizontal => (self.value_pos(v), self.top + along),
+        }
+    }
+
+    /// The rectangle `(x, y, w, h)` spanning `from..to` along the value axis
+    /// and `offset..offset + thickness` along the category axis.
+    pub fn rect(&self, offset: f32, thickness: f32, from: f32, to: f32) -> (f32, f32, f32, f32) {
+        let (a, b) = (self.value_pos(from), self.value_pos(to));
+        let (lo, hi) = (a.min(b), a.max(b));
+        match self.orientation {
+            Orientation::Vertical => (self.left + offset, lo, thickness, hi - lo),
+            Orientation::Horizontal => (lo, self.top + offset, hi - lo, thickness),
+        }
+    }
+
+    /// A line along the value axis from `lo` to `hi` at `center`, capped at both ends.
+    pub fn error_bar(&self, center: f32, cap: f32, lo: f32, hi: f32) -> Html {
+        let segment = |(x1, y1): (f32, f32), (x2, y2): (f32, f32)| {
+            format!("M{:.1} {:.1}L{:.1} {:.1}", x1, y1, x2, y2)
+        };
+        let d = [
+            segment(self.point(center, lo), self.point(center, hi)),
+            segment(self.point(center - cap, lo), self.point(center + cap, lo)),
+            segment(self.point(center - cap, hi), self.point(center + cap, hi)),
+        ]
+        .concat();
+        html! {
+            <path class="error-bar" d=d fill="none" stroke="#333333" stroke-width=1 pointer-events="none" />
+        }
+    }
+
+    /// Axes for the frame, drawn by the charts crate so they match `ChartComponent`.
+    pub fn axes(&self, categories: &[String], padding: f32) -> svg::node::element::Group {
+        let (top, right, bottom, left) = MARGINS;
+        let (width, height) = (
+            self.width as isize + left + right,
+            self.height as isize + top + bottom,
+        );
+        let band_range = match self.orientation {
+            Orientation::Vertical => vec![0, self.width as isize],
+            Orientation::Horizontal => vec![0, self.height as isize],
+        };
+        let bands = charts::ScaleBand::new()
+            .set_domain(categories.to_vec())
+            .set_range(band_range)
+            .set_inner_padding(padding)
+            .set_outer_padding(padding);
+        let values = charts::ScaleLinear::new().set_domain(vec![self.domain.0, self.domain.1]);
+        let c = charts::Chart::new()
+            .set_width(width)
+            .set_height(height)
+            .set_margins(top, right, bottom, left);
+        let c = match self.orientation {
+            Orientation::Vertical => {
+                let values = values.set_range(vec![self.height as isize, 0]);
+                c.add_axis_bottom(&bands).add_axis_left(&values).to_svg()
+            }
+            Orientation::Horizontal => {
+                let values = values.set_range(vec![0, self.width as isize]);
+                c.add_axis_left(&bands).add_axis_bottom(&values).to_svg()
+            }
+        };
+        c.unwrap()
+    }
+}
+
+/// The smallest domain holding every value and zero, so bars have a baseline.
+pub fn value_extent<I: IntoIterator<Item = f32>>(values: I) -> (f32, f32) {
+    let (lo, hi) = values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold((0.0f32, 0.0f32), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    match lo == hi {
+        true => (lo, lo + 1.0),
+        false => (lo, hi),
+    }
+}
+
+pub struct BarChart {
+    props: Props,
+    hidden: HashSet<String>,
+    hovered: Option<(usize, usize)>,
+    group: Option<svg::node::element::Group>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    /// `(series, category)` under the pointer
+    Hover(Option<(usize, usize)>),
+    Click(usize, usize),
+    ToggleSeries(String),
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    pub categories: Vec<String>,
+    /// Drawn side by side within each category, in order
+    pub series: Vec<BarSeries>,
+    #[prop_or(Orientation::Vertical)]
+    pub orientation: Orientation,
+    /// Fitted to the visible values and zero when unset
+    #[prop_or_default]
+    pub value_domain: Option<(f32, f32)>,
+    /// Fraction of each band left empty around the bars
+    #[prop_or(0.1)]
+    pub padding: f32,
+    /// Write each bar's value at its end
+    #[prop_or_default]
+    pub show_values: bool,
+    /// Stack the series in one bar per category instead of side by side
+    #[prop_or_default]
+    pub stack: Option<Stack>,
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<BarEvent>>>,
+    #[prop_or_default]
+    pub on_click: Option<Callback<BarEvent>>,
+}
+
+impl BarChart {
+    fn visible(&self) -> Vec<(usize, &BarSeries)> {
+        self.props
+            .series
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !self.hidden.contains(&s.name))
+            .collect()
+    }
+
+    /// Segments of the visible series in order, when stacked.
+    fn layers(&self) -> Option<Vec<Vec<Segment>>> {
+        let mode = self.props.stack?;
+        let values: Vec<Vec<f32>> = self.visible().into_iter().map(|(_, s)| s.values.clone()).collect();
+        Some(stack::stack(&values, mode))
+    }
+
+    fn frame(&self) -> Frame {
+        let domain = self.props.value_domain.unwrap_or_else(|| match self.layers() {
+            Some(layers) => stack::extent(&layers),
+            None => value_extent(self.visible().into_iter().flat_map(|(_, s)| {
+                let bounds = s.bounds.iter().flat_map(|(lo, hi)| vec![*lo, *hi]);
+                s.values.iter().cloned().chain(bounds)
+            })),
+        });
+        Frame::new(self.props.width, self.props.height, self.props.orientation, domain)
+    }
+
+    fn rebuild(&mut self) {
+        self.group = Some(self.frame().axes(&self.props.categories, self.props.padding));
+    }
+
+    fn event(&self, series: usize, category: usize) -> Option<BarEvent> {
+        let s = self.props.series.get(series)?;
+        let total = self
+            .visible()
+            .into_iter()
+            .filter_map(|(_, s)| s.values.get(category).cloned())
+            .filter(|v| v.is_finite())
+            .sum();
+        Some(BarEvent {
+            category: self.props.categories.get(category)?.clone(),
+            series: s.name.clone(),
+            value: *s.values.get(category)?,
+            total,
+            bounds: s.bounds.get(category).cloned(),
+        })
+    }
+
+    fn bar_view(&self, si: usize, ci: usize, rect: (f32, f32, f32, f32), label: Html) -> Html {
+        let (x, y, w, h) = rect;
+        let hovered = self.hovered == Some((si, ci));
+        html! {
+            <g class="bar">
+                <rect
+                    x=x y=y width=w height=h
+                    fill=series::palette_color(self.props.series[si].color.as_deref(), si)
+                    stroke=if hovered { "#333333" } else { "none" }
+                    style="cursor: pointer"
+                    onmouseenter=self.link.callback(move |_| Msg::Hover(Some((si, ci))))
+                    onmouseleave=self.link.callback(|_| Msg::Hover(None))
+                    onclick=self.link.callback(move |_| Msg::Click(si, ci))
+                />
+                {label}
+            </g>
+        }
+    }
+
+    fn stacked_view(&self, layers: Vec<Vec<Segment>>) -> Html {
+        let frame = self.frame();
+        let bands = Bands::new(self.props.categories.len(), frame.band_length(), self.props.padding);
+        let bars = self.visible().into_iter().zip(layers).flat_map(|((si, _), layer)| {
+            let (frame, bands) = (&frame, &bands);
+            layer
+                .into_iter()
+                .take(self.props.categories.len())
+                .enumerate()
+                .filter(|(_, seg)| seg.from != seg.to)
+                .map(move |(ci, seg)| {
+                    let label = match self.props.show_values {
+                        true => self.segment_label(frame, bands.offset(ci) + bands.bandwidth / 2.0, &seg),
+                        false => html! {},
+                    };
+                    let rect = frame.rect(bands.offset(ci), bands.bandwidth, seg.from, seg.to);
+                    self.bar_view(si, ci, rect, label)
+                })
+        });
+        html! {
+            <g class="bars">{ for bars }</g>
+        }
+    }
+
+    fn bars_view(&self) -> Html {
+        if let Some(layers) = self.layers() {
+            return self.stacked_view(layers);
+        }
+        let frame = self.frame();
+        let bands = Bands::new(self.props.categories.len(), frame.band_length(), self.props.padding);
+        let visible = self.visible();
+        let thickness = bands.bandwidth / visible.len().max(1) as f32;
+        let bars = self.props.categories.iter().enumerate().flat_map(|(ci, _)| {
+            let bands = &bands;
+            let frame = &frame;
+            visible.iter().enumerate().filter_map(move |(slot, (si, s))| {
+                let value = *s.values.get(ci).filter(|v| v.is_finite())?;
+                let offset = bands.offset(ci) + thickness * slot as f32;
+                let center = offset + thickness / 2.0;
+                let label = match self.props.show_values {
+                    true => self.value_label(frame, center, value),
+                    false => html! {},
+                };
+                let error = match s.bounds.get(ci).filter(|(lo, hi)| lo.is_finite() && hi.is_finite()) {
+                    Some((lo, hi)) => frame.error_bar(center, thickness / 4.0, *lo, *hi),
+                    None => html! {},
+                };
+                let extras = html! { <>{label}{error}</> };
+                Some(self.bar_view(*si, ci, frame.rect(offset, thickness, 0.0, value), extras))
+            })
+        });
+        html! {
+            <g class="bars">{ for bars }</g>
+        }
+    }
+
+    fn value_label(&self, frame: &Frame, center: f32, value: f32) -> Html {
+        let end = frame.value_pos(value);
+        let text = format!("{}", value);
+        match frame.orientation {
+            Orientation::Vertical => {
+                let y = if value < 0.0 { end + 14.0 } else { end - 4.0 };
+                html! {
+                    <text x=frame.left + center y=y text-anchor="middle" font-family="sans-serif" font-size=11 pointer-events="none">
+                        {text}
+                    </text>
+                }
+            }
+            Orientation::Horizontal => {
+                let (x, anchor) = if value < 0.0 { (end - 4.0, "end") } else { (end + 4.0, "start") };
+                html! {
+                    <text x=x y=frame.top + center + 4.0 text-anchor=anchor font-family="sans-serif" font-size=11 pointer-events="none">
+                        {text}
+                    </text>
+                }
+            }
+        }
+    }
+
+    /// A stacked segment's value, written in its middle.
+    fn segment_label(&self, frame: &Frame, center: f32, seg: &Segment) -> Html {
+        let mid = (frame.value_pos(seg.from) + frame.value_pos(seg.to)) / 2.0;
+        let (x, y) = match frame.orientation {
+            Orientation::Vertical => (frame.left + center, mid + 4.0),
+            Orientation::Horizontal => (mid, frame.top + center + 4.0),
+        };
+        html! {
+            <text x=x y=y text-anchor="middle" font-family="sans-serif" font-size=11 pointer-events="none">
+                {format!("{}", seg.value)}
+            </text>
+        }
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let e = match self.hovered.and_then(|(s, c)| self.event(s, c)) {
+            Some(e) => e,
+            None => return html! {},
+        };
+        let text = match (self.props.stack, e.bounds) {
+            (Some(_), _) => format!("{} / {}: {} of {}", e.category, e.series, e.value, e.total),
+            (None, Some((lo, hi))) => format!("{} / {}: {} ({} to {})", e.category, e.series, e.value, lo, hi),
+            (None, None) => format!("{} / {}: {}", e.category, e.series, e.value),
+        };
+        render::tooltip_text(self.props.width, text)
+    }
+
+    fn legend_view(&self) -> Html {
+        let (top, _, _, left) = MARGINS;
+        let entries = self
+            .props
+            .series
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.name.is_empty())
+            .map(|(idx, s)| LegendEntry {
+                name: s.name.clone(),
+                color: series::palette_color(s.color.as_deref(), idx),
+                faded: self.hidden.contains(&s.name),
+            })
+            .collect();
+        render::legend(
+            entries,
+            (left as f32, top as f32 - 20.0),
+            &self.link.callback(Msg::ToggleSeries),
+            None,
+        )
+    }
+}
+
+impl Component for BarChart {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut chart = Self {
+            group: None,
+            props,
+            hidden: HashSet::new(),
+            hovered: None,
+            link,
+        };
+        chart.rebuild();
+        chart
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Hover(bar) => {
+                if bar == self.hovered {
+                    return false;
+                }
+                self.hovered = bar;
+                if let Some(cb) = &self.props.on_hover {
+                    cb.emit(bar.and_then(|(s, c)| self.event(s, c)));
+                }
+            }
+            Msg::Click(s, c) => {
+                if let (Some(cb), Some(e)) = (&self.props.on_click, self.event(s, c)) {
+                    cb.emit(e);
+                }
+                return false;
+            }
+            Msg::ToggleSeries(name) => {
+                if !self.hidden.remove(&name) {
+                    self.hidden.insert(name);
+                }
+                self.hovered = None;
+                self.rebuild();
+            }
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.hovered = None;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let root = render::svg_root(self.props.width, self.props.height);
+        let mut node = render::svg_to_vtag(self.group.as_ref().unwrap(), root, |_, _| {});
+        node.add_child(self.bars_view());
+        node.add_child(self.legend_view());
+        node.add_child(self.tooltip_view());
+        node.into()
+    }
+}
@@ -0,0 +1,253 @@
+//! Renders chart specs to svg or png without a browser.
+//!
+//! ```text
+//! yew-charts render spec.json --data data.csv -o out.png
+//! yew-charts render specs/ -o reports/ --format png
+//! ```
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use yew_charts::spec::{ChartSpec, DataSpec, Row, SpecError};
+use yew_charts::table::Table;
+
+const USAGE: &str = "usage: yew-charts render <spec.json | dir> [--data <file>] [-o <out.svg | out.png | dir>] [--format svg|png]
+
+Renders one spec, or every *.json spec in a directory. In batch mode a spec
+without --data picks up a sibling <name>.csv, <name>.tsv or <name>.data.json.";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Svg,
+    Png,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "svg" => Some(Format::Svg),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+        }
+    }
+}
+
+enum Error {
+    Usage(String),
+    Io(PathBuf, std::io::Error),
+    Spec(PathBuf, SpecError),
+    Data(PathBuf, String),
+    Png(PathBuf, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => write!(f, "{}\n\n{}", msg, USAGE),
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Spec(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Data(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Png(path, e) => write!(f, "{}: failed to rasterize: {}", path.display(), e),
+        }
+    }
+}
+
+impl Error {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+struct Args {
+    input: PathBuf,
+    data: Option<PathBuf>,
+    output: Option<PathBuf>,
+    format: Option<Format>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, Error> {
+    match args.next().as_deref() {
+        Some("render") => {}
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Some(cmd) => return Err(Error::Usage(format!("unknown command `{}`", cmd))),
+        None => return Err(Error::Usage("missing command".to_string())),
+    }
+    let mut input = None;
+    let mut data = None;
+    let mut output = None;
+    let mut format = None;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .ok_or_else(|| Error::Usage(format!("{} needs a value", flag)))
+        };
+        match arg.as_str() {
+            "--data" | "-d" => data = Some(PathBuf::from(value(&arg)?)),
+            "--output" | "-o" => output = Some(PathBuf::from(value(&arg)?)),
+            "--format" | "-f" => {
+                let name = value(&arg)?;
+                format = Some(
+                    Format::from_name(&name)
+                        .ok_or_else(|| Error::Usage(format!("unknown format `{}`", name)))?,
+                );
+            }
+            flag if flag.starts_with('-') => {
+                return Err(Error::Usage(format!("unknown option `{}`", flag)))
+            }
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => return Err(Error::Usage(format!("unexpected argument `{}`", extra))),
+        }
+    }
+    Ok(Args {
+        input: input.ok_or_else(|| Error::Usage("missing spec".to_string()))?,
+        data,
+        output,
+        format,
+    })
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))
+}
+
+/// Reads `.csv`/`.tsv` files with a header row, or a json array of records.
+fn load_rows(path: &Path) -> Result<Vec<Row>, Error> {
+    let err = |reason: String| Error::Data(path.to_path_buf(), reason);
+    if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        return serde_json::from_str(&read(path)?).map_err(|e| err(e.to_string()));
+    }
+    Table::from_path(path)
+        .map(|table| table.records())
+        .map_err(|e| err(e.to_string()))
+}
+
+fn render(spec_path: &Path, data: Option<&Path>, out: &Path, format: Format) -> Result<(), Error> {
+    let mut spec = ChartSpec::from_json(&read(spec_path)?).map_err(|e| Error::Spec(spec_path.to_path_buf(), e))?;
+    if let Some(data) = data {
+        spec.data = Some(DataSpec {
+            values: load_rows(data)?,
+        });
+    }
+    let document = spec
+        .to_document()
+        .map_err(|e| Error::Spec(spec_path.to_path_buf(), e))?;
+    match format {
+        Format::Svg => fs::write(out, document.to_string()).map_err(|e| Error::Io(out.to_path_buf(), e)),
+        Format::Png => rasterize(&document.to_string(), out),
+    }
+}
+
+fn rasterize(svg: &str, out: &Path) -> Result<(), Error> {
+    let err = |reason: String| Error::Png(out.to_path_buf(), reason);
+    let mut opt = usvg::Options::default();
+    opt.fontdb.load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &opt.to_ref()).map_err(|e| err(e.to_string()))?;
+    let size = tree.svg_node().size.to_screen_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or_else(|| err("empty image".to_string()))?;
+    resvg::render(
+        &tree,
+        usvg::FitTo::Original,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )
+    .ok_or_else(|| err("nothing to render".to_string()))?;
+    pixmap.save_png(out).map_err(|e| err(e.to_string()))
+}
+
+/// The data file a spec in batch mode picks up when `--data` isn't given.
+fn sibling_data(spec: &Path) -> Option<PathBuf> {
+    let stem = spec.file_stem()?.to_str()?;
+    ["csv", "tsv", "data.json"]
+        .iter()
+        .map(|ext| spec.with_file_name(format!("{}.{}", stem, ext)))
+        .find(|path| path.is_file())
+}
+
+/// The format of a single output file: `format` if given, else the one its
+/// extension names, else svg. Asking for png in a `.svg` file is an error
+/// rather than a misnamed file.
+fn output_format(output: Option<&Path>, format: Option<Format>) -> Result<Format, Error> {
+    let named = output.and_then(|out| out.extension()).and_then(|e| e.to_str()).and_then(Format::from_name);
+    match (named, format) {
+        (Some(named), Some(format)) if named != format => Err(Error::Usage(format!(
+            "--format {} doesn't match the output file {}",
+            format.extension(),
+            output.unwrap_or_else(|| Path::new("")).display()
+        ))),
+        (named, format) => Ok(format.or(named).unwrap_or(Format::Svg)),
+    }
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let Args {
+        input,
+        data,
+        output,
+        format,
+    } = args;
+    if !input.is_dir() {
+        let format = output_format(output.as_deref(), format)?;
+        let out = output.unwrap_or_else(|| input.with_extension(format.extension()));
+        return render(&input, data.as_deref(), &out, format);
+    }
+
+    let format = format.unwrap_or(Format::Svg);
+    let out_dir = output.unwrap_or_else(|| input.clone());
+    fs::create_dir_all(&out_dir).map_err(|e| Error::Io(out_dir.clone(), e))?;
+    let mut specs: Vec<PathBuf> = fs::read_dir(&input)
+        .map_err(|e| Error::Io(input.clone(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".json") && !name.ends_with(".data.json")
+        })
+        .collect();
+    specs.sort();
+    if specs.is_empty() {
+        return Err(Error::Usage(format!("no *.json specs in {}", input.display())));
+    }
+    // keep going so one bad spec doesn't hide the others
+    let mut failed = 0;
+    for spec in &specs {
+        let data = data.clone().or_else(|| sibling_data(spec));
+        let stem = spec.file_stem().and_then(|s| s.to_str()).unwrap_or("chart");
+        let out = out_dir.join(format!("{}.{}", stem, format.extension()));
+        match render(spec, data.as_deref(), &out, format) {
+            Ok(()) => println!("{} -> {}", spec.display(), out.display()),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                failed += 1;
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(Error::Data(
+            input,
+            format!("{} of {} specs failed to render", n, specs.len()),
+        )),
+    }
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1)).and_then(run);
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(e.exit_code());
+    }
+}
@@ -0,0 +1,347 @@
+use std::rc::Rc;
+use yew::prelude::*;
+
+use crate::bar::{Bands, Frame, Orientation};
+use crate::histogram::{kde, silverman};
+use crate::render;
+use crate::series;
+use crate::stats::{quantile, sorted};
+
+// how many points each violin's outline is sampled at
+const VIOLIN_POINTS: usize = 100;
+const OUTLIER_RADIUS: f32 = 2.5;
+
+/// The raw samples of one category.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    pub label: String,
+    pub samples: Vec<f64>,
+    /// Hex color such as `#FF0000`; picked from `PALETTE` when unset
+    pub color: Option<String>,
+}
+
+impl Group {
+    pub fn new<S: Into<String>>(label: S, samples: Vec<f64>) -> Self {
+        Self {
+            label: label.into(),
+            samples,
+            color: None,
+        }
+    }
+
+    pub fn with_color<S: Into<String>>(mut self, color: S) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// What a box plot shows of a sample.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+    /// The furthest samples within the whisker range of the quartiles
+    pub low_whisker: f64,
+    pub high_whisker: f64,
+    /// Samples beyond the whiskers, in ascending order
+    pub outliers: Vec<f64>,
+}
+
+impl Summary {
+    /// Summarizes the finite `samples`, with whiskers reaching up to
+    /// `whisker` times the interquartile range past the quartiles (1.5 is
+    /// Tukey's rule). `None` if there are no samples.
+    pub fn new(samples: &[f64], whisker: f64) -> Option<Self> {
+        let sorted = sorted(samples);
+        let (min, max) = (*sorted.first()?, *sorted.last()?);
+        let (q1, median, q3) = (quantile(&sorted, 0.25)?, quantile(&sorted, 0.5)?, quantile(&sorted, 0.75)?);
+        let reach = (q3 - q1) * whisker.max(0.0);
+        let (lo, hi) = (q1 - reach, q3 + reach);
+        let inside = || sorted.iter().cloned().filter(|v| *v >= lo && *v <= hi);
+        Some(Self {
+            count: sorted.len(),
+            min,
+            q1,
+            median,
+            q3,
+            max,
+            // the quartiles are interpolated, so nothing may fall between them
+            low_whisker: inside().next().unwrap_or(q1).min(q1),
+            high_whisker: inside().next_back().unwrap_or(q3).max(q3),
+            outliers: sorted.iter().cloned().filter(|v| *v < lo || *v > hi).collect(),
+        })
+    }
+}
+
+/// The kernel density of `samples` from their smallest to largest value, as
+/// `(value, density)` pairs.
+pub fn violin(samples: &[f64], bandwidth: f64) -> Vec<(f64, f64)> {
+    let sorted = sorted(samples);
+    let (lo, hi) = match (sorted.first(), sorted.last()) {
+        (Some(lo), Some(hi)) => (*lo, *hi),
+        _ => return Vec::new(),
+    };
+    let xs: Vec<f64> = match lo == hi {
+        true => vec![lo],
+        false => {
+            let step = (hi - lo) / (VIOLIN_POINTS - 1) as f64;
+            (0..VIOLIN_POINTS).map(|i| lo + step * i as f64).collect()
+        }
+    };
+    let ys = kde(&sorted, bandwidth, &xs);
+    xs.into_iter().zip(ys).collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Quartile box, whiskers and outlier points
+    Box,
+    /// Mirrored density with a thin quartile box and the median inside
+    Violin,
+}
+
+pub struct BoxPlot {
+    props: Props,
+    summaries: Vec<Option<Summary>>,
+    violins: Vec<Vec<(f64, f64)>>,
+    axes: Option<svg::node::element::Group>,
+    hovered: Option<usize>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    Hover(Option<usize>),
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    /// One box or violin per group, in order along the category axis
+    pub groups: Rc<Vec<Group>>,
+    #[prop_or(Kind::Box)]
+    pub kind: Kind,
+    #[prop_or(Orientation::Vertical)]
+    pub orientation: Orientation,
+    /// Whisker reach in interquartile ranges
+    #[prop_or(1.5)]
+    pub whisker: f64,
+    /// Kernel bandwidth for violins; Silverman's rule per group when unset
+    #[prop_or_default]
+    pub bandwidth: Option<f64>,
+    /// Fitted to the samples when unset
+    #[prop_or_default]
+    pub value_domain: Option<(f32, f32)>,
+    /// Fraction of each band left empty around the shapes
+    #[prop_or(0.2)]
+    pub padding: f32,
+    /// The group label and its summary under the pointer
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<(String, Summary)>>>,
+}
+
+impl BoxPlot {
+    fn rebuild(&mut self) {
+        let whisker = self.props.whisker;
+        self.summaries = self.props.groups.iter().map(|g| Summary::new(&g.samples, whisker)).collect();
+        self.violins = match self.props.kind {
+            Kind::Violin => self
+                .props
+                .groups
+                .iter()
+                .map(|g| {
+                    let bandwidth = self
+                        .props
+                        .bandwidth
+                        .filter(|b| *b > 0.0)
+                        .unwrap_or_else(|| silverman(&g.samples));
+                    violin(&g.samples, bandwidth)
+                })
+                .collect(),
+            Kind::Box => Vec::new(),
+        };
+        self.hovered = None;
+        let labels: Vec<String> = self.props.groups.iter().map(|g| g.label.clone()).collect();
+        self.axes = Some(self.frame().axes(&labels, self.props.padding));
+    }
+
+    fn frame(&self) -> Frame {
+        let domain = self.props.value_domain.unwrap_or_else(|| {
+            let (lo, hi) = self
+                .summaries
+                .iter()
+                .flatten()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| (lo.min(s.min), hi.max(s.max)));
+            match (lo.is_finite(), lo < hi) {
+                (false, _) => (0.0, 1.0),
+                (true, false) => (lo as f32 - 1.0, hi as f32 + 1.0),
+                (true, true) => {
+                    let pad = (hi - lo) * 0.05;
+                    ((lo - pad) as f32, (hi + pad) as f32)
+                }
+            }
+        });
+        Frame::new(self.props.width, self.props.height, self.props.orientation, domain)
+    }
+
+    fn box_view(&self, frame: &Frame, offset: f32, thickness: f32, s: &Summary, color: &str) -> Html {
+        let center = offset + thickness / 2.0;
+        let cap = thickness / 4.0;
+        let line = |(a, va): (f32, f64), (b, vb): (f32, f64)| {
+            let (x1, y1) = frame.point(a, va as f32);
+            let (x2, y2) = frame.point(b, vb as f32);
+            html! { <line x1=x1 y1=y1 x2=x2 y2=y2 stroke="#333333" stroke-width=1 /> }
+        };
+        let (x, y, w, h) = frame.rect(offset, thickness, s.q1 as f32, s.q3 as f32);
+        let outliers = s.outliers.iter().map(|v| {
+            let (cx, cy) = frame.point(center, *v as f32);
+            html! { <circle cx=cx cy=cy r=OUTLIER_RADIUS fill="none" stroke=color /> }
+        });
+        html! {
+            <>
+                {line((center, s.low_whisker), (center, s.q1))}
+                {line((center, s.q3), (center, s.high_whisker))}
+                {line((center - cap, s.low_whisker), (center + cap, s.low_whisker))}
+                {line((center - cap, s.high_whisker), (center + cap, s.high_whisker))}
+                <rect x=x y=y width=w height=h fill=color stroke="#333333" stroke-width=1 />
+                {line((offset, s.median), (offset + thickness, s.median))}
+                { for outliers }
+            </>
+        }
+    }
+
+    fn violin_view(&self, frame: &Frame, offset: f32, thickness: f32, idx: usize, s: &Summary, color: &str) -> Html {
+        let curve = &self.violins[idx];
+        // one density scale for every violin, so their widths compare
+        let densest = self.violins.iter().flatten().fold(0.0f64, |hi, (_, d)| hi.max(*d));
+        let center = offset + thickness / 2.0;
+        let half = |d: f64| match densest > 0.0 {
+            true => (d / densest) as f32 * thickness / 2.0,
+            false => 0.0,
+        };
+        let points = curve
+            .iter()
+            .map(|(v, d)| frame.point(center + half(*d), *v as f32))
+            .chain(curve.iter().rev().map(|(v, d)| frame.point(center - half(*d), *v as f32)))
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let inner = (thickness / 10.0).max(2.0);
+        let (x, y, w, h) = frame.rect(center - inner / 2.0, inner, s.q1 as f32, s.q3 as f32);
+        let (mx, my) = frame.point(center, s.median as f32);
+        html! {
+            <>
+                <polygon points=points fill=color stroke="#333333" stroke-width=1 />
+                <rect x=x y=y width=w height=h fill="#333333" />
+                <circle cx=mx cy=my r=inner.min(4.0) fill="#FFFFFF" />
+            </>
+        }
+    }
+
+    fn groups_view(&self) -> Html {
+        let frame = self.frame();
+        let bands = Bands::new(self.props.groups.len(), frame.band_length(), self.props.padding);
+        let groups = self.props.groups.iter().enumerate().filter_map(|(idx, g)| {
+            let s = self.summaries[idx].as_ref()?;
+            let (offset, thickness) = (bands.offset(idx), bands.bandwidth);
+            let color = series::palette_color(g.color.as_deref(), idx);
+            let shape = match self.props.kind {
+                Kind::Box => self.box_view(&frame, offset, thickness, s, &color),
+                Kind::Violin => self.violin_view(&frame, offset, thickness, idx, s, &color),
+            };
+            // the whole band reacts to the pointer, not just the thin whiskers
+            let (lo, hi) = frame.domain;
+            let (x, y, w, h) = frame.rect(offset, thickness, lo, hi);
+            let hovered = self.hovered == Some(idx);
+            Some(html! {
+                <g
+                    class="group"
+                    onmouseenter=self.link.callback(move |_| Msg::Hover(Some(idx)))
+                    onmouseleave=self.link.callback(|_| Msg::Hover(None))
+                >
+                    <rect x=x y=y width=w height=h fill="#000000" fill-opacity=if hovered { 0.05 } else { 0.0 } />
+                    {shape}
+                </g>
+            })
+        });
+        html! {
+            <g class="groups">{ for groups }</g>
+        }
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let (label, s) = match self.hovered.and_then(|i| Some((&self.props.groups[i].label, self.summaries[i].as_ref()?))) {
+            Some(hovered) => hovered,
+            None => return html! {},
+        };
+        let text = format!(
+            "{}: median {}, quartiles {} to {}, whiskers {} to {}, {} outliers, n = {}",
+            label,
+            number(s.median),
+            number(s.q1),
+            number(s.q3),
+            number(s.low_whisker),
+            number(s.high_whisker),
+            s.outliers.len(),
+            s.count
+        );
+        render::tooltip_text(self.props.width, text)
+    }
+}
+
+// at most three decimals, without trailing zeros
+fn number(v: f64) -> String {
+    let s = format!("{:.3}", v);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl Component for BoxPlot {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut plot = Self {
+            props,
+            summaries: Vec::new(),
+            violins: Vec::new(),
+            axes: None,
+            hovered: None,
+            link,
+        };
+        plot.rebuild();
+        plot
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Hover(idx) => {
+                if idx == self.hovered {
+                    return false;
+                }
+                self.hovered = idx;
+                if let Some(cb) = &self.props.on_hover {
+                    let event = idx.and_then(|i| {
+                        let s = self.summaries[i].clone()?;
+                        Some((self.props.groups[i].label.clone(), s))
+                    });
+                    cb.emit(event);
+                }
+            }
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let root = render::svg_root(self.props.width, self.props.height);
+        let mut node = render::svg_to_vtag(self.axes.as_ref().unwrap(), root, |_, _| {});
+        node.add_child(self.groups_view());
+        node.add_child(self.tooltip_view());
+        node.into()
+    }
+}
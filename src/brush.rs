@@ -0,0 +1,226 @@
+use yew::prelude::*;
+
+use crate::view::{Domain, Projection};
+
+// half-width of the grab zone around each brush edge, in svg units
+const HANDLE: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrushMode {
+    /// Select an x range spanning the whole plot height
+    X,
+    /// Select a free rectangle
+    XY,
+}
+
+/// What `on_brush` reports once a brush is released.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrushSelection {
+    pub domain: Domain,
+    /// `(series, index)` of every point inside `domain`
+    pub points: Vec<(usize, usize)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Grab {
+    Create((f32, f32)),
+    Move,
+    Edge(Edge),
+}
+
+/// Rubber-band selection state shared by the chart components.
+///
+/// The selected range is kept in data space so it stays on the same data
+/// while the view is zoomed or panned.
+pub struct Brush {
+    pub mode: BrushMode,
+    range: Option<Domain>,
+    grab: Option<(Grab, (f32, f32))>,
+}
+
+impl Brush {
+    pub fn new(mode: BrushMode) -> Self {
+        Self {
+            mode,
+            range: None,
+            grab: None,
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.grab.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.range = None;
+        self.grab = None;
+    }
+
+    /// The selected range, with the y extent of `X` brushes filled in from `proj`.
+    pub fn domain(&self, proj: &Projection) -> Option<Domain> {
+        self.range.map(|r| match self.mode {
+            BrushMode::X => Domain::new(r.x, proj.domain.y),
+            BrushMode::XY => r,
+        })
+    }
+
+    fn pixel_rect(&self, proj: &Projection) -> Option<(f32, f32, f32, f32)> {
+        let d = self.domain(proj)?;
+        let (x0, y0) = proj.to_pixel((d.x.0, d.y.1));
+        let (x1, y1) = proj.to_pixel((d.x.1, d.y.0));
+        Some((x0, y0, x1, y1))
+    }
+
+    fn hit(&self, (px, py): (f32, f32), proj: &Projection) -> Grab {
+        let (x0, y0, x1, y1) = match self.pixel_rect(proj) {
+            Some(r) => r,
+            None => return Grab::Create((px, py)),
+        };
+        let in_x = px >= x0 - HANDLE && px <= x1 + HANDLE;
+        let in_y = py >= y0 - HANDLE && py <= y1 + HANDLE;
+        if in_y && (px - x0).abs() <= HANDLE {
+            Grab::Edge(Edge::Left)
+        } else if in_y && (px - x1).abs() <= HANDLE {
+            Grab::Edge(Edge::Right)
+        } else if self.mode == BrushMode::XY && in_x && (py - y0).abs() <= HANDLE {
+            Grab::Edge(Edge::Top)
+        } else if self.mode == BrushMode::XY && in_x && (py - y1).abs() <= HANDLE {
+            Grab::Edge(Edge::Bottom)
+        } else if px > x0 && px < x1 && py > y0 && py < y1 {
+            Grab::Move
+        } else {
+            Grab::Create((px, py))
+        }
+    }
+
+    pub fn start(&mut self, p: (f32, f32), proj: &Projection) {
+        let grab = self.hit(p, proj);
+        if let Grab::Create(_) = grab {
+            self.range = None;
+        }
+        self.grab = Some((grab, p));
+    }
+
+    /// Returns true if the brush changed and needs a redraw.
+    pub fn drag(&mut self, p: (f32, f32), proj: &Projection) -> bool {
+        let (grab, last) = match self.grab {
+            Some(g) => g,
+            None => return false,
+        };
+        let clamp = |(px, py): (f32, f32)| {
+            (
+                px.max(proj.left).min(proj.left + proj.width),
+                py.max(proj.top).min(proj.top + proj.height),
+            )
+        };
+        let (x, y) = proj.to_data(clamp(p));
+        let range = match (grab, self.range) {
+            (Grab::Create(origin), _) => {
+                let (ox, oy) = proj.to_data(clamp(origin));
+                Domain::new((ox.min(x), ox.max(x)), (oy.min(y), oy.max(y)))
+            }
+            (Grab::Move, Some(r)) => {
+                let (dx, dy) = proj.delta_to_data((p.0 - last.0, p.1 - last.1));
+                let d = proj.domain;
+                // keep the brush inside the plot while moving it
+                let dx = dx.max(d.x.0 - r.x.0).min(d.x.1 - r.x.1);
+                let dy = dy.max(d.y.0 - r.y.0).min(d.y.1 - r.y.1);
+                r.pan((dx, dy))
+            }
+            (Grab::Edge(edge), Some(r)) => {
+                let mut r = r;
+                match edge {
+                    Edge::Left => r.x.0 = x,
+                    Edge::Right => r.x.1 = x,
+                    Edge::Top => r.y.1 = y,
+                    Edge::Bottom => r.y.0 = y,
+                }
+                Domain::new((r.x.0.min(r.x.1), r.x.0.max(r.x.1)), (r.y.0.min(r.y.1), r.y.0.max(r.y.1)))
+            }
+            _ => return false,
+        };
+        // dragging an edge past its opposite swaps which edge is held
+        let grab = match grab {
+            Grab::Edge(Edge::Left) if x > range.x.0 => Grab::Edge(Edge::Right),
+            Grab::Edge(Edge::Right) if x < range.x.1 => Grab::Edge(Edge::Left),
+            Grab::Edge(Edge::Top) if y < range.y.1 => Grab::Edge(Edge::Bottom),
+            Grab::Edge(Edge::Bottom) if y > range.y.0 => Grab::Edge(Edge::Top),
+            g => g,
+        };
+        self.grab = Some((grab, p));
+        self.range = Some(range);
+        true
+    }
+
+    /// Finishes a drag, returning the selected domain if there is one.
+    pub fn end(&mut self, proj: &Projection) -> Option<Domain> {
+        self.grab.take()?;
+        let (x0, y0, x1, y1) = self.pixel_rect(proj)?;
+        // a click without a drag clears the selection
+        if x1 - x0 < 1.0 || (self.mode == BrushMode::XY && y1 - y0 < 1.0) {
+            self.range = None;
+            return None;
+        }
+        self.domain(proj)
+    }
+
+    /// Indices of the points inside the selection.
+    pub fn select<I: IntoIterator<Item = (f32, f32)>>(&self, proj: &Projection, points: I) -> Vec<usize> {
+        let d = match self.domain(proj) {
+            Some(d) => d,
+            None => return Vec::new(),
+        };
+        points
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (x, y))| match self.mode {
+                BrushMode::X => *x >= d.x.0 && *x <= d.x.1,
+                BrushMode::XY => d.contains((*x, *y)),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn view(&self, proj: &Projection) -> Html {
+        let (x0, y0, x1, y1) = match self.pixel_rect(proj) {
+            Some(r) => r,
+            None => return html! {},
+        };
+        let handle = |x: f32, y: f32, width: f32, height: f32| {
+            html! {
+                <rect class="brush-handle" x=x y=y width=width height=height fill="#4682B4" opacity=0.6 />
+            }
+        };
+        let mut handles = vec![
+            handle(x0 - HANDLE / 2.0, y0, HANDLE, y1 - y0),
+            handle(x1 - HANDLE / 2.0, y0, HANDLE, y1 - y0),
+        ];
+        if self.mode == BrushMode::XY {
+            handles.push(handle(x0, y0 - HANDLE / 2.0, x1 - x0, HANDLE));
+            handles.push(handle(x0, y1 - HANDLE / 2.0, x1 - x0, HANDLE));
+        }
+        html! {
+            <g class="brush">
+                <rect
+                    class="brush-selection"
+                    x=x0
+                    y=y0
+                    width=x1 - x0
+                    height=y1 - y0
+                    fill="#4682B4"
+                    fill-opacity=0.2
+                    stroke="#4682B4"
+                />
+                { for handles.into_iter() }
+            </g>
+        }
+    }
+}
@@ -1,20 +1,85 @@
-use svg::node::element::tag::Type;
-use svg::parser::{Event, Parser};
-use wasm_bindgen::prelude::*;
 use yew::prelude::*;
 use yew::services::ConsoleService;
-use yew::virtual_dom::{VList, VNode, VTag, VText};
+use yew::virtual_dom::VTag;
+use std::collections::HashSet;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use crate::brush::{Brush, BrushMode, BrushSelection};
+use crate::curve::{self, Area, Curve};
+use crate::gesture::{Gesture, TouchTracker};
+use crate::render::{self, LegendEntry};
+use crate::series::{self, Series};
+use crate::stack::{self, Segment, Stack};
+use crate::stream::StreamHandle;
+use crate::sync::{SyncAgent, SyncEvent, SyncInput};
+use crate::view::{self, Domain, Projection};
+
+// top, right, bottom, left
+pub(crate) const MARGINS: (isize, isize, isize, isize) = (90, 40, 50, 60);
+// how much one wheel notch scales the visible domain
+const ZOOM_STEP: f32 = 1.2;
+// how long a finger must rest before a tooltip shows
+const LONG_PRESS: Duration = Duration::from_millis(500);
+// furthest a point may be from a press to get a tooltip, in svg units
+const TOOLTIP_RADIUS: f32 = 30.0;
+
+// numbers each chart's clip path, whose id has to be unique in the page
+static CHARTS: AtomicUsize = AtomicUsize::new(0);
+
 pub struct ChartComponent {
     pub props: Props,
     pub width: isize,
     pub height: isize,
-    group: Option<svg::node::element::Group>,
+    domain: Domain,
+    drag: Option<(f32, f32)>,
+    brush: Option<Brush>,
+    touches: TouchTracker,
+    press_task: Option<TimeoutTask>,
+    tooltip: Option<Tooltip>,
+    crosshair: Option<f32>,
+    data_series: Series,
+    hidden: HashSet<String>,
+    highlight: Option<String>,
+    sync: Option<Box<dyn Bridge<SyncAgent>>>,
+    streamed: Vec<Series>,
+    stream_x: Option<(f32, f32)>,
+    stream_version: u64,
+    frame_task: Option<IntervalTask>,
+    /// The axes drawn by the charts crate, converted once per rebuild
+    chart: Html,
+    clip_id: String,
+    node_ref: NodeRef,
     link: ComponentLink<Self>,
 }
 
+/// A point picked for the tooltip.
+struct Tooltip {
+    /// Where it is drawn, in data units
+    at: (f32, f32),
+    /// What `on_hover` is called with
+    datum: (isize, isize),
+    text: String,
+}
+
 pub enum Msg {
     Hover(yew::MouseEvent),
+    Wheel(yew::WheelEvent),
+    DragStart(yew::MouseEvent),
+    MouseMove(yew::MouseEvent),
+    MouseLeave,
+    DragEnd,
+    TouchStart(yew::TouchEvent),
+    TouchMove(yew::TouchEvent),
+    TouchEnd(yew::TouchEvent),
+    LongPress,
+    Sync(SyncEvent),
+    ToggleSeries(String),
+    HighlightSeries(Option<String>),
+    Frame,
+    ResetView,
     Nothing,
 }
 
@@ -30,45 +95,571 @@ pub struct Props {
     pub on_hover: Option<Callback<(isize, isize)>>,
     #[prop_or_default]
     pub data: Vec<(isize, isize)>,
+    #[prop_or((0.0, 200.0))]
+    pub x_domain: (f32, f32),
+    #[prop_or((0.0, 100.0))]
+    pub y_domain: (f32, f32),
+    /// Keep the x axis fixed while zooming and panning
+    #[prop_or_default]
+    pub lock_x: bool,
+    /// Keep the y axis fixed while zooming and panning
+    #[prop_or_default]
+    pub lock_y: bool,
+    /// Smallest zoom level relative to `x_domain`/`y_domain`
+    #[prop_or(1.0)]
+    pub min_zoom: f32,
+    /// Largest zoom level relative to `x_domain`/`y_domain`
+    #[prop_or(100.0)]
+    pub max_zoom: f32,
+    /// Called when the view is zoomed, panned or reset, here or in a synced
+    /// chart. Refitting to new data or following a stream doesn't count.
+    #[prop_or_default]
+    pub on_view_change: Option<Callback<Domain>>,
+    /// Dragging selects a range instead of panning when set
+    #[prop_or_default]
+    pub brush: Option<BrushMode>,
+    #[prop_or_default]
+    pub on_brush: Option<Callback<BrushSelection>>,
+    /// Charts sharing a group name share their crosshair and x range
+    #[prop_or_default]
+    pub sync_group: Option<String>,
+    /// Named series drawn after `data` and listed in the legend
+    #[prop_or_default]
+    pub series: Vec<Series>,
+    /// Fit the domain to the visible series instead of `x_domain`/`y_domain`
+    #[prop_or_default]
+    pub auto_domain: bool,
+    /// Names of hidden series. When set, legend clicks are only reported
+    /// through `on_series_toggle` and the parent decides what is shown.
+    #[prop_or_default]
+    pub hidden_series: Option<Vec<String>>,
+    /// Called with a series name and whether it should now be visible
+    #[prop_or_default]
+    pub on_series_toggle: Option<Callback<(String, bool)>>,
+    /// Live series drawn after `series`; the x domain scrolls with the
+    /// stream's retention window unless the view was zoomed or panned
+    #[prop_or_default]
+    pub stream: Option<StreamHandle>,
+    /// Most redraws per second while streaming
+    #[prop_or(30)]
+    pub max_fps: u32,
+    /// Draw the series as areas layered on each other in order
+    #[prop_or_default]
+    pub stack: Option<Stack>,
 }
 
-impl Component for ChartComponent {
-    type Message = Msg;
-    type Properties = Props;
-    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let (width, height) = (props.width, props.height);
-        let (top, right, bottom, left) = (90, 40, 50, 60);
+impl ChartComponent {
+    /// Every series on the chart; `data` counts as an unnamed first series when given.
+    fn all_series(&self) -> Vec<(usize, &Series)> {
+        let unnamed = match self.props.data.is_empty() {
+            true => None,
+            false => Some(&self.data_series),
+        };
+        unnamed
+            .into_iter()
+            .chain(self.props.series.iter())
+            .chain(self.streamed.iter())
+            .enumerate()
+            .collect()
+    }
+
+    fn visible_series(&self) -> Vec<(usize, &Series)> {
+        self.all_series()
+            .into_iter()
+            .filter(|(_, s)| self.is_visible(&s.name))
+            .collect()
+    }
+
+    fn is_visible(&self, name: &str) -> bool {
+        match &self.props.hidden_series {
+            Some(hidden) => !hidden.iter().any(|h| h == name),
+            None => !self.hidden.contains(name),
+        }
+    }
+
+    /// The visible series stacked on the union of their x values, when `stack` is set.
+    fn layers(&self) -> Option<(Vec<isize>, Vec<(usize, &Series, Vec<Segment>)>)> {
+        let mode = self.props.stack?;
+        let visible = self.visible_series();
+        let (xs, values) = stack::align(visible.iter().map(|(_, s)| *s));
+        let layers = visible
+            .into_iter()
+            .zip(stack::stack(&values, mode))
+            .map(|((idx, s), layer)| (idx, s, layer))
+            .collect();
+        Some((xs, layers))
+    }
+
+    fn base_domain(&self) -> Domain {
+        let fitted = match self.props.auto_domain {
+            true => Series::extent(self.visible_series().into_iter().map(|(_, s)| s)),
+            false => None,
+        };
+        let mut base = fitted.unwrap_or_else(|| Domain::new(self.props.x_domain, self.props.y_domain));
+        if let Some((_, layers)) = self.layers() {
+            let layers: Vec<Vec<Segment>> = layers.into_iter().map(|(_, _, l)| l).collect();
+            let (lo, hi) = stack::extent(&layers);
+            match (self.props.stack, self.props.auto_domain) {
+                // whole halves, so the axis reads as shares either side of zero
+                (Some(Stack::Percent), _) => {
+                    base.y = (if lo < 0.0 { -100.0 } else { 0.0 }, if hi > 0.0 { 100.0 } else { 0.0 })
+                }
+                (Some(Stack::Normal), true) => base.y = (lo, hi),
+                _ => {}
+            }
+        }
+        if let Some(x) = self.stream_x {
+            base.x = x;
+        }
+        base
+    }
+
+    fn start_frames(props: &Props, link: &ComponentLink<Self>) -> Option<IntervalTask> {
+        props.stream.as_ref()?;
+        let period = Duration::from_millis(1000 / props.max_fps.max(1) as u64);
+        Some(IntervalService::spawn(period, link.callback(|_| Msg::Frame)))
+    }
+
+    fn build(&self) -> svg::node::element::Group {
+        let (width, height) = (self.width, self.height);
+        let domain = &self.domain;
+        let (top, right, bottom, left) = MARGINS;
         let x = charts::ScaleLinear::new()
-            .set_domain(vec![0f32, 200f32])
+            .set_domain(vec![domain.x.0, domain.x.1])
             .set_range(vec![0, width - left - right]);
         let y = charts::ScaleLinear::new()
-            .set_domain(vec![0f32, 100f32])
+            .set_domain(vec![domain.y.0, domain.y.1])
             .set_range(vec![height - top - bottom, 0]);
-        //let line_data = vec![(12, 54), (100, 40), (120, 50), (180, 70)];
-        let line_view = charts::LineSeriesView::new()
-            .set_x_scale(&x)
-            .set_y_scale(&y)
-            .set_marker_type(charts::MarkerType::Circle)
-            .set_label_position(charts::PointLabelPosition::N)
-            .load_data(&props.data)
-            .unwrap();
-        let c = charts::Chart::new()
+        charts::Chart::new()
             .set_width(width)
             .set_height(height)
             .set_margins(top, right, bottom, left)
             .add_title("Line Chart".to_string())
-            .add_view(&line_view)
             .add_axis_bottom(&x)
             .add_axis_left(&y)
             .add_left_axis_label("Custom Y axis")
-            .add_bottom_axis_label("Custom bottom");
-        Self {
+            .add_bottom_axis_label("Custom bottom")
+            .to_svg()
+            .unwrap()
+    }
+
+    /// Redraws what the charts crate draws. Only needed when the domain or
+    /// the size change; series are drawn on every render.
+    fn rebuild(&mut self) {
+        let on_hover = self.props.on_hover.as_ref().map(|_| self.link.callback(Msg::Hover));
+        let node = render::svg_to_vtag(&self.build(), VTag::new("g"), |attrs, vnode| {
+            let mouseable = attrs.get("class").map(|e| e.eq("scatter-point")).unwrap_or(false);
+            if let (true, Some(cb)) = (mouseable, &on_hover) {
+                vnode.add_listener(Rc::new(yew::html::onmouseover::Wrapper::new(cb.clone())));
+            }
+        });
+        self.chart = node.into();
+    }
+
+    /// A series' color, dimmed while another one is highlighted.
+    fn series_color(&self, idx: usize, s: &Series) -> String {
+        let color = s.color_or_default(idx);
+        match self.highlight.as_ref().map(|h| *h != s.name).unwrap_or(false) {
+            true => series::dim(&color),
+            false => color,
+        }
+    }
+
+    /// The points of `s` that are drawn, in svg units. Lines leaving the plot
+    /// area keep their next point and are clipped there.
+    fn pixel_points(&self, s: &Series) -> Vec<(f32, f32)> {
+        let proj = self.projection();
+        s.data[self.domain.visible(&s.data, |(x, _)| *x as f32)]
+            .iter()
+            .map(|(x, y)| proj.to_pixel((*x as f32, *y as f32)))
+            .collect()
+    }
+
+    fn clip_path(&self) -> String {
+        format!("url(#{})", self.clip_id)
+    }
+
+    /// The plot area, which lines, areas and bands are clipped to.
+    fn clip_view(&self) -> Html {
+        let proj = self.projection();
+        html! {
+            <defs>
+                <clipPath id=&self.clip_id>
+                    <rect x=proj.left y=proj.top width=proj.width height=proj.height />
+                </clipPath>
+            </defs>
+        }
+    }
+
+    /// Moves to the fitted domain if the visible series changed it, redrawing either way.
+    fn refit(&mut self, old_base: Domain) {
+        let base = self.base_domain();
+        if base == old_base || !self.apply_domain(base) {
+            self.rebuild();
+        }
+    }
+
+    fn legend_view(&self) -> Html {
+        let (top, _, _, left) = MARGINS;
+        let entries = self
+            .all_series()
+            .into_iter()
+            .filter(|(_, s)| !s.name.is_empty())
+            .map(|(idx, s)| LegendEntry {
+                name: s.name.clone(),
+                color: s.color_or_default(idx),
+                faded: !self.is_visible(&s.name),
+            })
+            .collect();
+        render::legend(
+            entries,
+            (left as f32, top as f32 - 20.0),
+            &self.link.callback(Msg::ToggleSeries),
+            Some(&self.link.callback(Msg::HighlightSeries)),
+        )
+    }
+
+    fn projection(&self) -> Projection {
+        let (top, right, bottom, left) = MARGINS;
+        Projection {
+            left: left as f32,
+            top: top as f32,
+            width: (self.width - left - right) as f32,
+            height: (self.height - top - bottom) as f32,
+            domain: self.domain,
+        }
+    }
+
+    fn svg_point(&self, e: &yew::MouseEvent) -> Option<(f32, f32)> {
+        self.client_to_svg((e.client_x(), e.client_y()))
+    }
+
+    fn client_to_svg(&self, client: (i32, i32)) -> Option<(f32, f32)> {
+        let view_box = (
+            self.props.min_x as f32,
+            self.props.min_y as f32,
+            self.width as f32,
+            self.height as f32,
+        );
+        view::client_to_svg(&self.node_ref, view_box, client)
+    }
+
+    fn touch_points(&self, e: &yew::TouchEvent) -> Vec<(f32, f32)> {
+        let touches = e.touches();
+        (0..touches.length())
+            .filter_map(|i| touches.get(i))
+            .filter_map(|t| self.client_to_svg((t.client_x(), t.client_y())))
+            .collect()
+    }
+
+    /// Zooms around a point given in svg units, honouring axis locks and zoom limits.
+    fn zoom(&mut self, anchor: (f32, f32), step: f32) -> ShouldRender {
+        let factor = (
+            if self.props.lock_x { 1.0 } else { step },
+            if self.props.lock_y { 1.0 } else { step },
+        );
+        let domain = self.domain.zoom_at(
+            &self.base_domain(),
+            self.projection().to_data(anchor),
+            factor,
+            (self.props.min_zoom, self.props.max_zoom),
+        );
+        self.set_domain(domain)
+    }
+
+    /// Pans so the data under the pointer follows a movement given in svg units.
+    fn pan(&mut self, delta: (f32, f32)) -> ShouldRender {
+        let (dx, dy) = self.projection().delta_to_data(delta);
+        let delta = (
+            if self.props.lock_x { 0.0 } else { -dx },
+            if self.props.lock_y { 0.0 } else { -dy },
+        );
+        let domain = self.domain.pan(delta);
+        self.set_domain(domain)
+    }
+
+    fn nearest_point(&self, p: (f32, f32)) -> Option<Tooltip> {
+        let proj = self.projection();
+        let candidates: Vec<Tooltip> = match self.layers() {
+            // stacked points sit at the top of their segment
+            Some((xs, layers)) => layers
+                .into_iter()
+                .flat_map(|(_, s, layer)| {
+                    let xs = &xs;
+                    layer.into_iter().zip(xs).map(move |(seg, x)| Tooltip {
+                        at: (*x as f32, seg.to),
+                        datum: (*x, seg.value.round() as isize),
+                        text: format!("{}: {} of {}", s.name, seg.value, seg.total),
+                    })
+                })
+                .collect(),
+            None => self
+                .visible_series()
+                .into_iter()
+                .flat_map(|(_, s)| s.data.iter().enumerate().map(move |(i, p)| (p, s.bounds.get(i))))
+                .map(|((x, y), bounds)| Tooltip {
+                    at: (*x as f32, *y as f32),
+                    datum: (*x, *y),
+                    text: match bounds {
+                        Some((lo, hi)) => format!("({}, {}) [{}, {}]", x, y, lo, hi),
+                        None => format!("({}, {})", x, y),
+                    },
+                })
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|t| self.domain.contains(t.at))
+            .map(|t| {
+                let (px, py) = proj.to_pixel(t.at);
+                (((px - p.0).powi(2) + (py - p.1).powi(2)).sqrt(), t)
+            })
+            .filter(|(dist, _)| *dist <= TOOLTIP_RADIUS)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map(|(_, t)| t)
+    }
+
+    /// Shaded bands between the bounds of the visible series, drawn under the lines.
+    fn bands_view(&self) -> Html {
+        if self.props.stack.is_some() {
+            return html! {};
+        }
+        let proj = self.projection();
+        let pixel = |x: isize, y: isize| proj.to_pixel((x as f32, y as f32));
+        let bands = self.visible_series().into_iter().filter_map(|(idx, s)| {
+            let bounded: Vec<(isize, (isize, isize))> =
+                s.data.iter().zip(&s.bounds).map(|((x, _), bounds)| (*x, *bounds)).collect();
+            let inside = &bounded[self.domain.visible(&bounded, |(x, _)| *x as f32)];
+            if inside.len() < 2 {
+                return None;
+            }
+            // along the upper bounds and back along the lower ones
+            let points = inside
+                .iter()
+                .map(|(x, (_, hi))| pixel(*x, *hi))
+                .chain(inside.iter().rev().map(|(x, (lo, _))| pixel(*x, *lo)))
+                .map(|(px, py)| format!("{:.1},{:.1}", px, py))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let color = s.color_or_default(idx);
+            let dimmed = self.highlight.as_ref().map(|h| *h != s.name).unwrap_or(false);
+            Some(html! {
+                <polygon class="band" points=points fill=color fill-opacity=if dimmed { 0.08 } else { 0.2 } stroke="none" />
+            })
+        });
+        html! {
+            <g class="bands" clip-path=self.clip_path() pointer-events="none">{ for bands }</g>
+        }
+    }
+
+    /// Stacked layers, each filled between its bottom and its top.
+    fn stacked_view(&self) -> Html {
+        let (xs, layers) = match self.layers() {
+            Some(stacked) => stacked,
+            None => return html! {},
+        };
+        let proj = self.projection();
+        let visible = self.domain.visible(&xs, |x| *x as f32);
+        let xs: Vec<f32> = xs[visible.clone()].iter().map(|x| *x as f32).collect();
+        let polygons = layers.into_iter().map(|(idx, s, layer)| {
+            let points: Vec<String> = stack::outline(&xs, &layer[visible.clone()])
+                .into_iter()
+                .map(|p| proj.to_pixel(p))
+                .map(|(px, py)| format!("{},{}", px, py))
+                .collect();
+            html! {
+                <polygon class="layer" points=points.join(" ") fill=self.series_color(idx, s) stroke="none" />
+            }
+        });
+        html! {
+            <g class="stacked" clip-path=self.clip_path() pointer-events="none">{ for polygons }</g>
+        }
+    }
+
+    /// Fills under the series with an `area`, drawn under the lines.
+    fn areas_view(&self) -> Html {
+        if self.props.stack.is_some() {
+            return html! {};
+        }
+        let proj = self.projection();
+        let all = self.all_series();
+        let areas = self.visible_series().into_iter().filter_map(|(idx, s)| {
+            let baseline: Option<Vec<(f32, f32)>> = match s.area.as_ref()? {
+                Area::Zero => None,
+                Area::Series(name) => {
+                    let (_, base) = all.iter().find(|(_, b)| b.name == *name)?;
+                    Some(base.data.iter().map(|(x, y)| (*x as f32, *y as f32)).collect())
+                }
+            };
+            let top = self.pixel_points(s);
+            // the baseline is sampled at the same xs, so both edges line up
+            let base: Vec<(f32, f32)> = s.data[self.domain.visible(&s.data, |(x, _)| *x as f32)]
+                .iter()
+                .map(|(x, _)| *x as f32)
+                .filter_map(|x| {
+                    let y = match &baseline {
+                        Some(points) => curve::value_at(points, x)?,
+                        None => 0.0,
+                    };
+                    Some(proj.to_pixel((x, y)))
+                })
+                .collect();
+            if top.len() < 2 || base.len() != top.len() {
+                return None;
+            }
+            let path = curve::area_path(&top, &base, s.curve);
+            Some(html! {
+                <path class="area" d=path fill=self.series_color(idx, s) fill-opacity=0.3 stroke="none" />
+            })
+        });
+        html! {
+            <g class="areas" clip-path=self.clip_path() pointer-events="none">{ for areas }</g>
+        }
+    }
+
+    /// Lines of the visible series, with markers on straight ones.
+    fn lines_view(&self) -> Html {
+        if self.props.stack.is_some() {
+            return html! {};
+        }
+        let lines = self.visible_series().into_iter().map(|(idx, s)| {
+            let color = self.series_color(idx, s);
+            let points = self.pixel_points(s);
+            let markers = match s.curve {
+                Curve::Linear => points.clone(),
+                _ => Vec::new(),
+            };
+            html! {
+                <g class="line">
+                    <path d=curve::line_path(&points, s.curve) fill="none" stroke=&color stroke-width=2 />
+                    { for markers.into_iter().map(|(cx, cy)| html! { <circle cx=cx cy=cy r=3 fill=&color /> }) }
+                </g>
+            }
+        });
+        html! {
+            <g class="lines" clip-path=self.clip_path() pointer-events="none">{ for lines }</g>
+        }
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let tooltip = match &self.tooltip {
+            Some(tooltip) => tooltip,
+            None => return html! {},
+        };
+        let (px, py) = self.projection().to_pixel(tooltip.at);
+        html! {
+            <g class="tooltip">
+                <circle cx=px cy=py r=6 fill="none" stroke="#333333" />
+                <text x=px y=py - 12.0 text-anchor="middle" font-family="sans-serif" font-size=12>
+                    {&tooltip.text}
+                </text>
+            </g>
+        }
+    }
+
+    /// Moves the view for the user, telling synced charts and `on_view_change`.
+    fn set_domain(&mut self, domain: Domain) -> ShouldRender {
+        if !self.apply_domain(domain) {
+            return false;
+        }
+        self.publish(SyncEvent::XRange(domain.x));
+        self.view_changed();
+        true
+    }
+
+    /// Moves the view without telling anyone, as when refitting to new data.
+    /// Reporting those would have a fetching parent refetch on every response.
+    fn apply_domain(&mut self, domain: Domain) -> ShouldRender {
+        if domain == self.domain {
+            return false;
+        }
+        self.domain = domain;
+        self.rebuild();
+        true
+    }
+
+    fn view_changed(&self) {
+        if let Some(cb) = &self.props.on_view_change {
+            cb.emit(self.domain);
+        }
+    }
+
+    fn join(props: &Props, link: &ComponentLink<Self>) -> Option<Box<dyn Bridge<SyncAgent>>> {
+        let group = props.sync_group.clone()?;
+        let mut bridge = SyncAgent::bridge(link.callback(Msg::Sync));
+        bridge.send(SyncInput::Join(group));
+        Some(bridge)
+    }
+
+    fn publish(&mut self, event: SyncEvent) {
+        if let Some(bridge) = &mut self.sync {
+            bridge.send(SyncInput::Publish(event));
+        }
+    }
+
+    fn set_crosshair(&mut self, x: Option<f32>) -> ShouldRender {
+        if x == self.crosshair {
+            return false;
+        }
+        self.crosshair = x;
+        self.publish(SyncEvent::Cursor(x));
+        true
+    }
+
+    fn crosshair_view(&self) -> Html {
+        let x = match self.crosshair {
+            Some(x) if x >= self.domain.x.0 && x <= self.domain.x.1 => x,
+            _ => return html! {},
+        };
+        let proj = self.projection();
+        let (px, _) = proj.to_pixel((x, self.domain.y.0));
+        html! {
+            <line
+                class="crosshair"
+                x1=px
+                y1=proj.top
+                x2=px
+                y2=proj.top + proj.height
+                stroke="#888888"
+                stroke-dasharray="4 2"
+                pointer-events="none"
+            />
+        }
+    }
+}
+
+impl Component for ChartComponent {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let (width, height) = (props.width, props.height);
+        let mut chart = Self {
+            chart: html! {},
+            data_series: Series::new("", props.data.clone()),
+            domain: Domain::new(props.x_domain, props.y_domain),
+            brush: props.brush.map(Brush::new),
+            sync: Self::join(&props, &link),
+            frame_task: Self::start_frames(&props, &link),
             props,
             width,
             height,
-            group: Some(c.to_svg().unwrap()),
+            drag: None,
+            touches: TouchTracker::default(),
+            press_task: None,
+            tooltip: None,
+            crosshair: None,
+            hidden: HashSet::new(),
+            highlight: None,
+            streamed: Vec::new(),
+            stream_x: None,
+            stream_version: 0,
+            clip_id: format!("chart-clip-{}", CHARTS.fetch_add(1, Ordering::Relaxed)),
+            node_ref: NodeRef::default(),
             link,
-        }
+        };
+        chart.domain = chart.base_domain();
+        chart.rebuild();
+        chart
     }
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
@@ -76,18 +667,215 @@ impl Component for ChartComponent {
    
                 ConsoleService::info(&format!("{:?}", e.target()));
             },
+            Msg::Wheel(e) => {
+                let proj = self.projection();
+                let p = match self.svg_point(&e) {
+                    Some(p) if proj.contains(p) => p,
+                    _ => return false,
+                };
+                e.prevent_default();
+                let step = if e.delta_y() < 0.0 { 1.0 / ZOOM_STEP } else { ZOOM_STEP };
+                return self.zoom(p, step);
+            },
+            Msg::DragStart(e) => {
+                let proj = self.projection();
+                let p = self.svg_point(&e).filter(|p| proj.contains(*p));
+                match (&mut self.brush, p) {
+                    (Some(brush), Some(p)) => {
+                        brush.start(p, &proj);
+                        return true;
+                    }
+                    _ => self.drag = p,
+                }
+                return false;
+            },
+            Msg::MouseMove(e) => {
+                // the crosshair follows the pointer through brushing too
+                let moved = match self.sync.is_some() && self.drag.is_none() {
+                    true => {
+                        let proj = self.projection();
+                        let x = self
+                            .svg_point(&e)
+                            .filter(|p| proj.contains(*p))
+                            .map(|p| proj.to_data(p).0);
+                        self.set_crosshair(x)
+                    }
+                    false => false,
+                };
+                if self.brush.as_ref().map(Brush::is_dragging).unwrap_or(false) {
+                    let proj = self.projection();
+                    let dragged = match (self.svg_point(&e), &mut self.brush) {
+                        (Some(p), Some(brush)) => brush.drag(p, &proj),
+                        _ => false,
+                    };
+                    return dragged || moved;
+                }
+                let (last, p) = match (self.drag, self.svg_point(&e)) {
+                    (Some(last), Some(p)) => (last, p),
+                    // stacked areas have no markers to hover, so follow the pointer
+                    (None, Some(p)) if self.props.stack.is_some() => {
+                        let tooltip = self.nearest_point(p);
+                        let changed = tooltip.as_ref().map(|t| t.datum) != self.tooltip.as_ref().map(|t| t.datum);
+                        self.tooltip = tooltip;
+                        return changed || moved;
+                    }
+                    _ => return moved,
+                };
+                self.drag = Some(p);
+                let panned = self.pan((p.0 - last.0, p.1 - last.1));
+                return panned || moved;
+            },
+            Msg::MouseLeave => {
+                let ended = self.update(Msg::DragEnd);
+                return self.set_crosshair(None) || ended;
+            },
+            Msg::DragEnd => {
+                self.drag = None;
+                let proj = self.projection();
+                let brush = match self.brush.as_mut().filter(|b| b.is_dragging()) {
+                    Some(brush) => brush,
+                    None => return false,
+                };
+                let domain = match brush.end(&proj) {
+                    Some(domain) => domain,
+                    None => return true,
+                };
+                let brush = self.brush.as_ref().unwrap();
+                let points = self
+                    .visible_series()
+                    .into_iter()
+                    .flat_map(|(idx, s)| {
+                        let points = s.data.iter().map(|(x, y)| (*x as f32, *y as f32));
+                        brush.select(&proj, points).into_iter().map(move |i| (idx, i))
+                    })
+                    .collect();
+                if let Some(cb) = &self.props.on_brush {
+                    cb.emit(BrushSelection { domain, points });
+                }
+            },
+            Msg::TouchStart(e) => {
+                let points = self.touch_points(&e);
+                self.touches.reset(points);
+                self.press_task = match self.touches.press() {
+                    Some(_) => Some(TimeoutService::spawn(LONG_PRESS, self.link.callback(|_| Msg::LongPress))),
+                    None => None,
+                };
+                return self.tooltip.take().is_some();
+            },
+            Msg::TouchMove(e) => {
+                let points = self.touch_points(&e);
+                let gesture = self.touches.update(points);
+                if self.touches.press().is_none() {
+                    self.press_task = None;
+                }
+                return match gesture {
+                    Some(Gesture::Pan(delta)) => self.pan(delta),
+                    Some(Gesture::Pinch { anchor, factor, shift }) => {
+                        let zoomed = self.zoom(anchor, factor);
+                        self.pan(shift) || zoomed
+                    }
+                    None => false,
+                };
+            },
+            Msg::TouchEnd(e) => {
+                let points = self.touch_points(&e);
+                self.touches.reset(points);
+                self.press_task = None;
+                return false;
+            },
+            Msg::LongPress => {
+                self.press_task = None;
+                self.tooltip = self.touches.press().and_then(|p| self.nearest_point(p));
+                if let (Some(tooltip), Some(cb)) = (&self.tooltip, &self.props.on_hover) {
+                    cb.emit(tooltip.datum);
+                }
+            },
+            Msg::Sync(SyncEvent::Cursor(x)) => {
+                if x == self.crosshair {
+                    return false;
+                }
+                self.crosshair = x;
+            },
+            Msg::Sync(SyncEvent::XRange(x)) => {
+                // a user moved a synced chart, so this counts as theirs too
+                let domain = Domain::new(x, self.domain.y);
+                if !self.apply_domain(domain) {
+                    return false;
+                }
+                self.view_changed();
+            },
+            Msg::ToggleSeries(name) => {
+                let visible = !self.is_visible(&name);
+                if let Some(cb) = &self.props.on_series_toggle {
+                    cb.emit((name.clone(), visible));
+                }
+                if self.props.hidden_series.is_some() {
+                    return false;
+                }
+                let old_base = self.base_domain();
+                if visible {
+                    self.hidden.remove(&name);
+                } else {
+                    self.hidden.insert(name);
+                }
+                self.refit(old_base);
+            },
+            Msg::HighlightSeries(name) => {
+                if name == self.highlight {
+                    return false;
+                }
+                self.highlight = name;
+            },
+            Msg::Frame => {
+                let stream = match &self.props.stream {
+                    Some(stream) if stream.version() != self.stream_version => stream.clone(),
+                    _ => return false,
+                };
+                // only scroll along if the user hasn't moved away from the live edge
+                let following = self.domain == self.base_domain();
+                self.stream_version = stream.version();
+                self.streamed = stream.snapshot();
+                self.stream_x = stream.x_extent();
+                let base = self.base_domain();
+                // new points alone only change the series, which aren't part of the rebuild
+                if following {
+                    self.apply_domain(base);
+                }
+            },
+            Msg::ResetView => {
+                if let Some(brush) = &mut self.brush {
+                    brush.clear();
+                }
+                let domain = self.base_domain();
+                return self.set_domain(domain);
+            },
             Msg::Nothing => {},
         }
         true
     }
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let old_base = self.base_domain();
+        if props.brush != self.props.brush {
+            self.brush = props.brush.map(Brush::new);
+        }
+        if props.stream != self.props.stream || props.max_fps != self.props.max_fps {
+            self.frame_task = Self::start_frames(&props, &self.link);
+            self.streamed.clear();
+            self.stream_x = None;
+            self.stream_version = 0;
+        }
+        if props.sync_group != self.props.sync_group {
+            self.sync = Self::join(&props, &self.link);
+            self.crosshair = None;
+        }
+        self.width = props.width;
+        self.height = props.height;
+        self.data_series.data = props.data.clone();
+        self.props = props;
+        self.refit(old_base);
+        true
     }
     fn view(&self) -> Html {
-        let s = self.group.as_ref().unwrap().to_string();
-        let buf = std::io::BufReader::new(s.as_bytes());
-        let svg = svg::read(buf).unwrap();
-        let mut stack: Vec<VTag> = Vec::new();
         let mut root = VTag::new("svg");
         root.add_attribute("width", &"100%");
         root.add_attribute("height", &"100%");
@@ -99,66 +887,31 @@ impl Component for ChartComponent {
             ),
         );
         root.add_attribute("preserveAspectRatio", &"none");
-        stack.push(root);
-        let mut data_idx = 0;
-        for event in svg {
-            match event {
-                Event::Tag(tag, kind, attrs) => {
-                    match kind {
-                        Type::End => {
-                            // done adding children to this tag
-                            let completed = stack.pop().unwrap();
-                            let parent = stack.last_mut().unwrap();
-                            parent.add_child(completed.into());
-                            continue;
-                        }
-                        Type::Start => {
-                            // have start, build tag and attrs
-                            let mut vnode = VTag::new(tag.to_owned());
-                            let mouseable = attrs.get("class").map(|e| e.eq("scatter-point")).unwrap_or(false);
-                            let mut attributes: Vec<(String, String)> = attrs
-                                .iter()
-                                .map(|(k, v)| (k.to_string(), v.to_string()))
-                                .collect();
-                            attributes.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-                            vnode.add_attributes(attributes);
-                            if self.props.on_hover.is_some() {
-                                let wrapper = yew::html::onmouseover::Wrapper::new(self.link.callback(Msg::Hover));
-                                if mouseable {
-                                    vnode.add_listener(Rc::new(wrapper));
-                                }
-                            }
-                            stack.push(vnode);
-                        }
-                        Type::Empty => {
-                            // "empty" tags have no children
-                            let mut vnode = VTag::new(tag.to_owned());
-                            let attributes = attrs
-                                .iter()
-                                .map(|(k, v)| (k.to_string(), v.to_string()))
-                                .collect();
-                            vnode.add_attributes(attributes);
-                            stack
-                                .last_mut()
-                                .expect("no parent for empty tag")
-                                .add_child(vnode.into());
-                        }
-                    }
-                }
-                Event::Text(s) => {
-                    let node = VText::new(s.to_string());
-                    stack
-                        .last_mut()
-                        .expect("no parent for text item")
-                        .add_child(node.into());
-                }
-                Event::Error(e) => ConsoleService::error(&format!("error parsing svg: {}", e)),
-                Event::Comment => ConsoleService::debug("got comment"),
-                Event::Declaration => ConsoleService::debug("got decl"),
-                Event::Instruction => ConsoleService::debug("go instruction"),
-            }
+        root.node_ref = self.node_ref.clone();
+        root.add_listener(Rc::new(yew::html::onwheel::Wrapper::new(self.link.callback(Msg::Wheel))));
+        root.add_listener(Rc::new(yew::html::onmousedown::Wrapper::new(self.link.callback(Msg::DragStart))));
+        root.add_listener(Rc::new(yew::html::onmousemove::Wrapper::new(self.link.callback(Msg::MouseMove))));
+        root.add_listener(Rc::new(yew::html::onmouseup::Wrapper::new(self.link.callback(|_| Msg::DragEnd))));
+        root.add_listener(Rc::new(yew::html::onmouseleave::Wrapper::new(self.link.callback(|_| Msg::MouseLeave))));
+        root.add_listener(Rc::new(yew::html::ondblclick::Wrapper::new(self.link.callback(|_| Msg::ResetView))));
+        // touch listeners are passive, so stop the browser scrolling and zooming the page instead
+        root.add_attribute("style", &"touch-action: none");
+        root.add_listener(Rc::new(yew::html::ontouchstart::Wrapper::new(self.link.callback(Msg::TouchStart))));
+        root.add_listener(Rc::new(yew::html::ontouchmove::Wrapper::new(self.link.callback(Msg::TouchMove))));
+        root.add_listener(Rc::new(yew::html::ontouchend::Wrapper::new(self.link.callback(Msg::TouchEnd))));
+        root.add_listener(Rc::new(yew::html::ontouchcancel::Wrapper::new(self.link.callback(Msg::TouchEnd))));
+        root.add_child(self.clip_view());
+        root.add_child(self.stacked_view());
+        root.add_child(self.areas_view());
+        root.add_child(self.bands_view());
+        root.add_child(self.chart.clone());
+        root.add_child(self.lines_view());
+        if let Some(brush) = &self.brush {
+            root.add_child(brush.view(&self.projection()));
         }
-        let node = stack.pop().unwrap().into();
-        node
+        root.add_child(self.legend_view());
+        root.add_child(self.crosshair_view());
+        root.add_child(self.tooltip_view());
+        root.into()
     }
 }
@@ -0,0 +1,381 @@
+//! A compact columnar format for shipping large numeric datasets.
+//!
+//! All integers are little-endian. A buffer is
+//!
+//! ```text
+//! b"YCOL" | version: u8 = 1 | 3 reserved bytes | rows: u32 | columns: u32
+//! per column: kind: u8 | reserved: u8 | name length: u16 | utf-8 name
+//! zero padding to a multiple of 8
+//! per column: `rows` values of its kind, zero padded to a multiple of 8
+//! ```
+//!
+//! where kind is 1 for f32, 2 for f64 and 3 for i64. Every column body starts
+//! 8-byte aligned, so on wasm it can be copied straight out of the fetched
+//! `ArrayBuffer` through a typed array view.
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+use thiserror::Error;
+
+use crate::fetch::BinaryMapFn;
+use crate::series::Series;
+use crate::table::Selection;
+
+const MAGIC: &[u8; 4] = b"YCOL";
+const VERSION: u8 = 1;
+const HEADER: usize = 16;
+
+#[derive(Error, Debug)]
+pub enum ColumnarError {
+    #[error("not a columnar buffer")]
+    Magic,
+    #[error("unsupported columnar version {0}")]
+    Version(u8),
+    #[error("column `{name}` has unknown kind {kind}")]
+    Kind { name: String, kind: u8 },
+    #[error("buffer is {len} bytes, expected at least {expected}")]
+    Truncated { len: usize, expected: usize },
+    #[error("column name is not utf-8")]
+    Name,
+    #[error("column `{name}` has {found} values, expected {expected}")]
+    Length {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("no column named `{0}`")]
+    NoColumn(String),
+    #[error("{what} is {value}, more than the format allows ({max})")]
+    TooLarge {
+        what: &'static str,
+        value: usize,
+        max: usize,
+    },
+    #[error("failed to write: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    I64(Vec<i64>),
+}
+
+impl Column {
+    fn kind(&self) -> u8 {
+        match self {
+            Column::F32(_) => 1,
+            Column::F64(_) => 2,
+            Column::I64(_) => 3,
+        }
+    }
+
+    fn width(kind: u8) -> Option<usize> {
+        match kind {
+            1 => Some(4),
+            2 | 3 => Some(8),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Column::F32(v) => v.len(),
+            Column::F64(v) => v.len(),
+            Column::I64(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, row: usize) -> Option<f64> {
+        match self {
+            Column::F32(v) => v.get(row).map(|v| *v as f64),
+            Column::F64(v) => v.get(row).cloned(),
+            Column::I64(v) => v.get(row).map(|v| *v as f64),
+        }
+    }
+
+    fn write_le<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        match self {
+            Column::F32(v) => v.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Column::F64(v) => v.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Column::I64(v) => v.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+        }
+    }
+
+    fn read_le(kind: u8, bytes: &[u8]) -> Option<Self> {
+        Some(match kind {
+            1 => Column::F32(bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()),
+            2 => Column::F64(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(eight(b))).collect()),
+            3 => Column::I64(bytes.chunks_exact(8).map(|b| i64::from_le_bytes(eight(b))).collect()),
+            _ => return None,
+        })
+    }
+}
+
+fn eight(b: &[u8]) -> [u8; 8] {
+    [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]
+}
+
+fn padded(len: usize) -> usize {
+    (len + 7) & !7
+}
+
+/// `offset + len` padded to 8, or `None` past `usize::MAX`. Sizes come from
+/// the buffer, so a crafted header could otherwise wrap, which on 32 bit
+/// targets such as wasm32 takes only a few gigabytes of claimed rows.
+fn checked_end(offset: usize, len: usize) -> Option<usize> {
+    offset.checked_add(len)?.checked_add(7).map(|end| end & !7)
+}
+
+struct Layout {
+    rows: usize,
+    /// `(name, kind, body offset)`
+    columns: Vec<(String, u8, usize)>,
+}
+
+impl Layout {
+    /// Reads the header through `read(offset, len)`, which must return
+    /// exactly `len` bytes or fail.
+    fn read<R>(len: usize, read: R) -> Result<Self, ColumnarError>
+    where
+        R: Fn(usize, usize) -> Result<Vec<u8>, ColumnarError>,
+    {
+        let head = read(0, HEADER)?;
+        if &head[..4] != MAGIC {
+            return Err(ColumnarError::Magic);
+        }
+        if head[4] != VERSION {
+            return Err(ColumnarError::Version(head[4]));
+        }
+        let rows = u32::from_le_bytes([head[8], head[9], head[10], head[11]]) as usize;
+        let count = u32::from_le_bytes([head[12], head[13], head[14], head[15]]) as usize;
+        // not sized by `count`, which is untrusted; each entry is read before it is kept
+        let mut offset = HEADER;
+        let mut headers = Vec::new();
+        for _ in 0..count {
+            let entry = read(offset, 4)?;
+            let name_len = u16::from_le_bytes([entry[2], entry[3]]) as usize;
+            let name = String::from_utf8(read(offset + 4, name_len)?).map_err(|_| ColumnarError::Name)?;
+            headers.push((name, entry[0]));
+            offset += 4 + name_len;
+        }
+        let mut offset = padded(offset);
+        let mut columns = Vec::with_capacity(headers.len());
+        for (name, kind) in headers {
+            let width = Column::width(kind).ok_or_else(|| ColumnarError::Kind {
+                name: name.clone(),
+                kind,
+            })?;
+            columns.push((name, kind, offset));
+            offset = rows
+                .checked_mul(width)
+                .and_then(|body| checked_end(offset, body))
+                .ok_or(ColumnarError::Truncated {
+                    len,
+                    expected: usize::MAX,
+                })?;
+            if offset > len {
+                return Err(ColumnarError::Truncated { len, expected: offset });
+            }
+        }
+        Ok(Self { rows, columns })
+    }
+}
+
+/// Equal-length named numeric columns.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Columns {
+    names: Vec<String>,
+    columns: Vec<Column>,
+}
+
+impl Columns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column, which must be as long as the ones already added.
+    pub fn add<S: Into<String>>(&mut self, name: S, column: Column) -> Result<(), ColumnarError> {
+        let name = name.into();
+        if let Some(first) = self.columns.first() {
+            if first.len() != column.len() {
+                return Err(ColumnarError::Length {
+                    name,
+                    expected: first.len(),
+                    found: column.len(),
+                });
+            }
+        }
+        self.names.push(name);
+        self.columns.push(column);
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.columns.first().map(Column::len).unwrap_or(0)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn column(&self, name: &str) -> Result<&Column, ColumnarError> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|idx| &self.columns[idx])
+            .ok_or_else(|| ColumnarError::NoColumn(name.to_string()))
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, ColumnarError> {
+        let mut out = Vec::new();
+        self.write_to(&mut out)?;
+        Ok(out)
+    }
+
+    /// Streams the encoded buffer, e.g. into a response body. Fails before
+    /// writing anything if a size doesn't fit its header field.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> Result<(), ColumnarError> {
+        let limit = |what: &'static str, value: usize, max: usize| match value > max {
+            true => Err(ColumnarError::TooLarge { what, value, max }),
+            false => Ok(()),
+        };
+        limit("row count", self.rows(), u32::MAX as usize)?;
+        limit("column count", self.columns.len(), u32::MAX as usize)?;
+        for name in &self.names {
+            limit("column name length", name.len(), u16::MAX as usize)?;
+        }
+        let pad = |out: &mut W, written: usize| out.write_all(&[0; 8][..padded(written) - written]);
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION, 0, 0, 0])?;
+        out.write_all(&(self.rows() as u32).to_le_bytes())?;
+        out.write_all(&(self.columns.len() as u32).to_le_bytes())?;
+        let mut written = HEADER;
+        for (name, column) in self.names.iter().zip(&self.columns) {
+            out.write_all(&[column.kind(), 0])?;
+            out.write_all(&(name.len() as u16).to_le_bytes())?;
+            out.write_all(name.as_bytes())?;
+            written += 4 + name.len();
+        }
+        pad(out, written)?;
+        for column in &self.columns {
+            column.write_le(out)?;
+            let width = Column::width(column.kind()).unwrap_or(8);
+            pad(out, column.len() * width)?;
+        }
+        Ok(())
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ColumnarError> {
+        let slice = |offset: usize, len: usize| {
+            let end = offset.saturating_add(len);
+            bytes.get(offset..end).map(<[u8]>::to_vec).ok_or(ColumnarError::Truncated {
+                len: bytes.len(),
+                expected: end,
+            })
+        };
+        let layout = Layout::read(bytes.len(), slice)?;
+        let mut columns = Self::new();
+        for (name, kind, offset) in layout.columns {
+            // `Layout::read` checked every body fits in the buffer
+            let width = Column::width(kind).unwrap_or(8);
+            let body = &bytes[offset..offset + layout.rows * width];
+            let column = Column::read_le(kind, body).ok_or_else(|| ColumnarError::Kind {
+                name: name.clone(),
+                kind,
+            })?;
+            columns.add(name, column)?;
+        }
+        Ok(columns)
+    }
+
+    /// Decodes straight from a fetched `ArrayBuffer`, copying each float
+    /// column out through a typed array view rather than the whole buffer.
+    pub fn from_array_buffer(buffer: &js_sys::ArrayBuffer) -> Result<Self, ColumnarError> {
+        let len = buffer.byte_length() as usize;
+        let bytes = |offset: usize, count: usize| {
+            let end = offset.saturating_add(count);
+            if end > len {
+                return Err(ColumnarError::Truncated { len, expected: end });
+            }
+            Ok(js_sys::Uint8Array::new_with_byte_offset_and_length(buffer, offset as u32, count as u32).to_vec())
+        };
+        let layout = Layout::read(len, bytes)?;
+        let mut columns = Self::new();
+        for (name, kind, offset) in layout.columns {
+            let (offset, rows) = (offset as u32, layout.rows as u32);
+            // typed arrays use the platform's byte order, which is little-endian everywhere wasm runs
+            let column = match kind {
+                1 => Column::F32(js_sys::Float32Array::new_with_byte_offset_and_length(buffer, offset, rows).to_vec()),
+                2 => Column::F64(js_sys::Float64Array::new_with_byte_offset_and_length(buffer, offset, rows).to_vec()),
+                _ => {
+                    let body = js_sys::Uint8Array::new_with_byte_offset_and_length(buffer, offset, rows * 8).to_vec();
+                    Column::read_le(kind, &body).ok_or_else(|| ColumnarError::Kind {
+                        name: name.clone(),
+                        kind,
+                    })?
+                }
+            };
+            columns.add(name, column)?;
+        }
+        Ok(columns)
+    }
+
+    /// `(x, y)` pairs for `ChartComponent`'s `data` prop.
+    pub fn points(&self, x: &str, y: &str) -> Result<Vec<(isize, isize)>, ColumnarError> {
+        let (xs, ys) = (self.column(x)?, self.column(y)?);
+        Ok((0..self.rows())
+            .filter_map(|row| Some((xs.get(row)?, ys.get(row)?)))
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|(x, y)| (x.round() as isize, y.round() as isize))
+            .collect())
+    }
+
+    /// Series for `ChartComponent`'s `series` prop, in order of first
+    /// appearance and each sorted by x. A split column is numeric here, so
+    /// series are named after its values.
+    pub fn series(&self, selection: &Selection) -> Result<Vec<Series>, ColumnarError> {
+        let split = match &selection.split {
+            Some(name) => Some(self.column(name)?),
+            None => None,
+        };
+        let (xs, ys) = (self.column(&selection.x)?, self.column(&selection.y)?);
+        let mut series: Vec<Series> = Vec::new();
+        // split values by their bits, so each is named once rather than per row
+        let mut index: HashMap<Option<u64>, usize> = HashMap::new();
+        for row in 0..self.rows() {
+            let (x, y) = match (xs.get(row), ys.get(row)) {
+                (Some(x), Some(y)) if x.is_finite() && y.is_finite() => (x, y),
+                _ => continue,
+            };
+            let key = split.and_then(|c| c.get(row));
+            let idx = *index.entry(key.map(f64::to_bits)).or_insert_with(|| {
+                let name = match key {
+                    Some(key) => key.to_string(),
+                    None => selection.y.clone(),
+                };
+                series.push(Series::new(name, Vec::new()));
+                series.len() - 1
+            });
+            series[idx].data.push((x.round() as isize, y.round() as isize));
+        }
+        for s in &mut series {
+            s.data.sort_by_key(|(x, _)| *x);
+        }
+        Ok(series)
+    }
+}
+
+/// A `FetchChart` `binary` mapper for endpoints serving this format.
+pub fn map_fn(selection: Selection) -> BinaryMapFn {
+    Rc::new(move |body: &[u8]| {
+        Columns::decode(body)
+            .and_then(|columns| columns.series(&selection))
+            .map_err(|e| e.to_string())
+    })
+}
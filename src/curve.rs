@@ -0,0 +1,172 @@
+//! How a series' line gets from one point to the next, as svg path data.
+use std::fmt::Write;
+
+/// Interpolation between consecutive points.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Curve {
+    /// Straight segments
+    #[default]
+    Linear,
+    /// Jump to the next value at the start of the step, as for state changes
+    StepBefore,
+    /// Hold each value until the next point, as for counters and gauges
+    StepAfter,
+    /// Jump halfway between the points
+    StepMiddle,
+    /// Smooth curves that never overshoot the points (monotone cubic)
+    Monotone,
+}
+
+/// What the space under a line is filled down to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Area {
+    Zero,
+    /// The series with this name, held at its first and last values beyond its ends
+    Series(String),
+}
+
+type Point = (f32, f32);
+
+/// One piece of a path, a line or a cubic with its two control points.
+#[derive(Clone, Copy, Debug)]
+struct Piece {
+    from: Point,
+    to: Point,
+    controls: Option<(Point, Point)>,
+}
+
+impl Piece {
+    fn line(from: Point, to: Point) -> Self {
+        Self { from, to, controls: None }
+    }
+
+    fn reversed(self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+            controls: self.controls.map(|(a, b)| (b, a)),
+        }
+    }
+
+    fn write(&self, d: &mut String) {
+        let _ = match self.controls {
+            Some((a, b)) => write!(
+                d,
+                "C{:.1} {:.1} {:.1} {:.1} {:.1} {:.1}",
+                a.0, a.1, b.0, b.1, self.to.0, self.to.1
+            ),
+            None => write!(d, "L{:.1} {:.1}", self.to.0, self.to.1),
+        };
+    }
+}
+
+fn pieces(points: &[Point], curve: Curve) -> Vec<Piece> {
+    let pairs = points.windows(2).map(|w| (w[0], w[1]));
+    match curve {
+        Curve::Linear => pairs.map(|(a, b)| Piece::line(a, b)).collect(),
+        Curve::StepBefore => pairs
+            .flat_map(|(a, b)| vec![Piece::line(a, (a.0, b.1)), Piece::line((a.0, b.1), b)])
+            .collect(),
+        Curve::StepAfter => pairs
+            .flat_map(|(a, b)| vec![Piece::line(a, (b.0, a.1)), Piece::line((b.0, a.1), b)])
+            .collect(),
+        Curve::StepMiddle => pairs
+            .flat_map(|(a, b)| {
+                let mid = (a.0 + b.0) / 2.0;
+                vec![
+                    Piece::line(a, (mid, a.1)),
+                    Piece::line((mid, a.1), (mid, b.1)),
+                    Piece::line((mid, b.1), b),
+                ]
+            })
+            .collect(),
+        Curve::Monotone => {
+            let tangents = tangents(points);
+            pairs
+                .zip(tangents.windows(2))
+                .map(|((a, b), t)| {
+                    let third = (b.0 - a.0) / 3.0;
+                    Piece {
+                        from: a,
+                        to: b,
+                        controls: Some(((a.0 + third, a.1 + t[0] * third), (b.0 - third, b.1 - t[1] * third))),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+// Fritsch-Carlson tangents, limited so each cubic stays within its two points
+fn tangents(points: &[Point]) -> Vec<f32> {
+    let n = points.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+    let secants: Vec<f32> = points
+        .windows(2)
+        .map(|w| match w[1].0 - w[0].0 {
+            h if h != 0.0 => (w[1].1 - w[0].1) / h,
+            _ => 0.0,
+        })
+        .collect();
+    let mut tangents = vec![0.0; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for i in 1..n - 1 {
+        let (s0, s1) = (secants[i - 1], secants[i]);
+        if s0 * s1 <= 0.0 {
+            continue;
+        }
+        let (h0, h1) = (points[i].0 - points[i - 1].0, points[i + 1].0 - points[i].0);
+        let p = (s0 * h1 + s1 * h0) / (h0 + h1);
+        tangents[i] = 2.0 * s0.signum() * s0.abs().min(s1.abs()).min(0.5 * p.abs());
+    }
+    tangents
+}
+
+/// Path data through `points`, which should be sorted by x.
+pub fn line_path(points: &[Point], curve: Curve) -> String {
+    let mut d = String::new();
+    if let Some((x, y)) = points.first() {
+        let _ = write!(d, "M{:.1} {:.1}", x, y);
+    }
+    for piece in pieces(points, curve) {
+        piece.write(&mut d);
+    }
+    d
+}
+
+/// A closed shape from `top` across to the end of `base` and back along it.
+/// Both edges follow `curve`.
+pub fn area_path(top: &[Point], base: &[Point], curve: Curve) -> String {
+    if top.is_empty() || base.is_empty() {
+        return String::new();
+    }
+    let mut d = line_path(top, curve);
+    let (x, y) = base[base.len() - 1];
+    let _ = write!(d, "L{:.1} {:.1}", x, y);
+    for piece in pieces(base, curve).into_iter().rev() {
+        piece.reversed().write(&mut d);
+    }
+    d.push('Z');
+    d
+}
+
+/// The y of `points` (sorted by x) at `x`, linearly interpolated and held at
+/// the first and last values beyond the ends.
+pub fn value_at(points: &[Point], x: f32) -> Option<f32> {
+    let (first, last) = (points.first()?, points.last()?);
+    if x <= first.0 {
+        return Some(first.1);
+    }
+    if x >= last.0 {
+        return Some(last.1);
+    }
+    let i = points.iter().position(|p| p.0 >= x)?;
+    let (a, b) = (points[i - 1], points[i]);
+    Some(match b.0 - a.0 {
+        h if h > 0.0 => a.1 + (b.1 - a.1) * (x - a.0) / h,
+        _ => b.1,
+    })
+}
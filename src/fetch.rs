@@ -0,0 +1,284 @@
+use std::rc::Rc;
+use std::time::Duration;
+use yew::format::{Binary, Nothing, Text};
+use yew::prelude::*;
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+
+use crate::chart::ChartComponent;
+use crate::series::Series;
+use crate::view::Domain;
+
+/// Builds the request for a url, given the visible domain once the user has zoomed or panned.
+pub type RequestFn = Rc<dyn Fn(&str, Option<Domain>) -> Request<Nothing>>;
+
+/// Turns a response body into chart series.
+pub type MapFn = Rc<dyn Fn(&str) -> Result<Vec<Series>, String>>;
+
+/// Turns a binary response body into chart series.
+pub type BinaryMapFn = Rc<dyn Fn(&[u8]) -> Result<Vec<Series>, String>>;
+
+fn no_map() -> MapFn {
+    Rc::new(|_| Err("no `map` for text responses".to_string()))
+}
+
+/// A plain GET of `url`, which fails if `url` isn't a valid uri.
+pub fn get(url: &str) -> Result<Request<Nothing>, String> {
+    Request::get(url)
+        .body(Nothing)
+        .map_err(|e| format!("invalid url `{}`: {}", url, e))
+}
+
+/// The series in a text response, or why there are none.
+pub fn text_series(response: Response<Text>, map: &MapFn) -> Result<Vec<Series>, String> {
+    body(response).and_then(|body| map(&body))
+}
+
+/// The series in a binary response, or why there are none.
+pub fn binary_series(response: Response<Binary>, decode: &BinaryMapFn) -> Result<Vec<Series>, String> {
+    body(response).and_then(|body| decode(&body))
+}
+
+fn body<T, E: std::fmt::Display>(response: Response<Result<T, E>>) -> Result<T, String> {
+    let (meta, body) = response.into_parts();
+    match body {
+        Ok(body) if meta.status.is_success() => Ok(body),
+        Ok(_) => Err(format!("request failed: {}", meta.status)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// wait for the view to settle before asking for data at the new resolution
+const VIEW_DEBOUNCE: Duration = Duration::from_millis(300);
+
+enum State {
+    Loading,
+    Loaded(Vec<Series>),
+    Failed(String),
+}
+
+/// A line chart that loads its series over HTTP.
+pub struct FetchChart {
+    props: Props,
+    state: State,
+    view: Option<Domain>,
+    refreshing: bool,
+    fetch_task: Option<FetchTask>,
+    refresh_task: Option<IntervalTask>,
+    debounce_task: Option<TimeoutTask>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    Fetch,
+    Fetched(Result<Vec<Series>, String>),
+    ViewChanged(Domain),
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    pub url: String,
+    #[prop_or_else(no_map)]
+    pub map: MapFn,
+    /// Fetches the body as bytes and decodes it with this instead of `map`
+    #[prop_or_default]
+    pub binary: Option<BinaryMapFn>,
+    /// Builds the request instead of a plain GET of `url`. Only a chart with
+    /// one of these re-fetches after zooming, since only it can ask for a domain.
+    #[prop_or_default]
+    pub request: Option<RequestFn>,
+    /// Re-fetch on this interval
+    #[prop_or_default]
+    pub refresh: Option<Duration>,
+    #[prop_or((0.0, 200.0))]
+    pub x_domain: (f32, f32),
+    #[prop_or((0.0, 100.0))]
+    pub y_domain: (f32, f32),
+    #[prop_or_default]
+    pub auto_domain: bool,
+    #[prop_or_default]
+    pub sync_group: Option<String>,
+}
+
+impl FetchChart {
+    fn fetch(&mut self) {
+        let request = match &self.props.request {
+            Some(build) => build(&self.props.url, self.view),
+            None => match get(&self.props.url) {
+                Ok(request) => request,
+                Err(e) => {
+                    self.refreshing = false;
+                    self.state = State::Failed(e);
+                    return;
+                }
+            },
+        };
+        self.refreshing = true;
+        let started = match &self.props.binary {
+            Some(decode) => {
+                let decode = decode.clone();
+                FetchService::fetch_binary(
+                    request,
+                    self.link
+                        .callback(move |response: Response<Binary>| Msg::Fetched(binary_series(response, &decode))),
+                )
+            }
+            None => {
+                let map = self.props.map.clone();
+                FetchService::fetch(
+                    request,
+                    self.link
+                        .callback(move |response: Response<Text>| Msg::Fetched(text_series(response, &map))),
+                )
+            }
+        };
+        match started {
+            Ok(task) => self.fetch_task = Some(task),
+            Err(e) => {
+                self.refreshing = false;
+                self.state = State::Failed(e.to_string());
+            }
+        }
+    }
+
+    fn start_refresh(props: &Props, link: &ComponentLink<Self>) -> Option<IntervalTask> {
+        let period = props.refresh?;
+        Some(IntervalService::spawn(period, link.callback(|_| Msg::Fetch)))
+    }
+
+    fn overlay(&self, body: Html) -> Html {
+        html! {
+            <div
+                class="fetch-overlay"
+                style="position: absolute; top: 0; left: 0; right: 0; bottom: 0; display: flex; align-items: center; justify-content: center; font: 14px sans-serif"
+            >
+                {body}
+            </div>
+        }
+    }
+}
+
+impl Component for FetchChart {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut chart = Self {
+            refresh_task: Self::start_refresh(&props, &link),
+            props,
+            state: State::Loading,
+            view: None,
+            refreshing: false,
+            fetch_task: None,
+            debounce_task: None,
+            link,
+        };
+        chart.fetch();
+        chart
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Fetch => {
+                if let State::Failed(_) = self.state {
+                    self.state = State::Loading;
+                }
+                self.debounce_task = None;
+                self.fetch();
+            }
+            Msg::Fetched(result) => {
+                self.fetch_task = None;
+                self.refreshing = false;
+                self.state = match result {
+                    Ok(series) => State::Loaded(series),
+                    Err(e) => State::Failed(e),
+                };
+            }
+            Msg::ViewChanged(domain) => {
+                self.view = Some(domain);
+                if self.props.request.is_none() {
+                    return false;
+                }
+                self.debounce_task = Some(TimeoutService::spawn(
+                    VIEW_DEBOUNCE,
+                    self.link.callback(|_| Msg::Fetch),
+                ));
+                return false;
+            }
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let refetch = props.url != self.props.url;
+        if props.refresh != self.props.refresh {
+            self.refresh_task = Self::start_refresh(&props, &self.link);
+        }
+        self.props = props;
+        if refetch {
+            self.view = None;
+            self.state = State::Loading;
+            self.fetch();
+        }
+        true
+    }
+    fn view(&self) -> Html {
+        let placeholder = html! {
+            <svg
+                width="100%"
+                height="100%"
+                viewBox=format!("0 0 {} {}", self.props.width, self.props.height)
+                preserveAspectRatio="none"
+            >
+                <rect x=0 y=0 width=self.props.width height=self.props.height fill="#F5F5F5" />
+            </svg>
+        };
+        let content = match &self.state {
+            State::Loading => html! {
+                <>
+                    {placeholder}
+                    {self.overlay(html! { <span class="fetch-loading">{"Loading…"}</span> })}
+                </>
+            },
+            State::Failed(e) => html! {
+                <>
+                    {placeholder}
+                    {self.overlay(html! {
+                        <div class="fetch-error" style="color: #D62728; text-align: center">
+                            <div>{e}</div>
+                            <button onclick=self.link.callback(|_| Msg::Fetch)>{"Retry"}</button>
+                        </div>
+                    })}
+                </>
+            },
+            State::Loaded(series) => html! {
+                <>
+                    <ChartComponent
+                        width=self.props.width
+                        height=self.props.height
+                        series=series.clone()
+                        x_domain=self.props.x_domain
+                        y_domain=self.props.y_domain
+                        auto_domain=self.props.auto_domain
+                        sync_group=self.props.sync_group.clone()
+                        on_view_change=Some(self.link.callback(Msg::ViewChanged))
+                    />
+                    {
+                        if self.refreshing {
+                            html! {
+                                <span class="fetch-loading" style="position: absolute; top: 4px; right: 8px; font: 12px sans-serif">
+                                    {"Loading…"}
+                                </span>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </>
+            },
+        };
+        html! {
+            <div class="fetch-chart" style="position: relative">{content}</div>
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// how far a finger may drift, in svg units, before a press becomes a pan
+const PRESS_SLOP: f32 = 8.0;
+
+/// A view change derived from touch movement, in svg units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// One finger moved by this delta
+    Pan((f32, f32)),
+    /// Two fingers pinched around `anchor`; `factor` below 1 zooms in
+    Pinch {
+        anchor: (f32, f32),
+        factor: f32,
+        shift: (f32, f32),
+    },
+}
+
+/// Turns successive touch positions into pans, pinches and presses.
+#[derive(Default)]
+pub struct TouchTracker {
+    points: Vec<(f32, f32)>,
+    origin: Option<(f32, f32)>,
+}
+
+fn midpoint(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+impl TouchTracker {
+    /// Records the touches currently on the surface after a start or end event.
+    pub fn reset(&mut self, points: Vec<(f32, f32)>) {
+        self.origin = match points.as_slice() {
+            [p] if self.points.is_empty() => Some(*p),
+            _ => None,
+        };
+        self.points = points;
+    }
+
+    pub fn update(&mut self, points: Vec<(f32, f32)>) -> Option<Gesture> {
+        let gesture = match (self.points.as_slice(), points.as_slice()) {
+            ([a], [b]) => {
+                if let Some(o) = self.origin {
+                    if distance(o, *b) <= PRESS_SLOP {
+                        return None;
+                    }
+                    self.origin = None;
+                }
+                Some(Gesture::Pan((b.0 - a.0, b.1 - a.1)))
+            }
+            ([a1, a2, ..], [b1, b2, ..]) => {
+                let (before, after) = (distance(*a1, *a2), distance(*b1, *b2));
+                if after == 0.0 {
+                    None
+                } else {
+                    let (m0, m1) = (midpoint(*a1, *a2), midpoint(*b1, *b2));
+                    Some(Gesture::Pinch {
+                        anchor: m0,
+                        factor: before / after,
+                        shift: (m1.0 - m0.0, m1.1 - m0.1),
+                    })
+                }
+            }
+            _ => None,
+        };
+        self.points = points;
+        gesture
+    }
+
+    /// Where a single finger is being held without moving, if it is.
+    pub fn press(&self) -> Option<(f32, f32)> {
+        match self.points.as_slice() {
+            [_] => self.origin,
+            _ => None,
+        }
+    }
+}
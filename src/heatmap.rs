@@ -0,0 +1,443 @@
+use plotters::drawing::DrawingBackend;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew::services::ConsoleService;
+use yew::virtual_dom::VTag;
+
+use crate::chart::MARGINS;
+use crate::render;
+use crate::series::{self, BLUE_RED, VIRIDIS};
+use crate::utils::VTagWrapper;
+use crate::view;
+
+// grids with more cells than this are drawn as one image under `Render::Auto`
+const BITMAP_CELLS: usize = 4096;
+// most labels along either axis before some are skipped
+const MAX_TICKS: usize = 12;
+// entries in the color lookup table used for bitmaps
+const LUT_SIZE: usize = 256;
+const COLOR_BAR_STEPS: usize = 32;
+
+/// Values on a grid of labelled columns (along x) and rows (along y, the
+/// first at the bottom).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Grid {
+    pub columns: Vec<String>,
+    pub rows: Vec<String>,
+    /// `values[row][column]`; anything not finite is an empty cell
+    pub values: Vec<Vec<f64>>,
+}
+
+impl Grid {
+    pub fn new(columns: Vec<String>, rows: Vec<String>, values: Vec<Vec<f64>>) -> Self {
+        Self { columns, rows, values }
+    }
+
+    /// One column per distinct x and one row per distinct y, both sorted.
+    /// Triples landing on the same cell are summed, so raw events can be
+    /// counted straight into buckets.
+    pub fn from_triples(triples: &[(f64, f64, f64)]) -> Self {
+        let distinct = |key: fn(&(f64, f64, f64)) -> f64| {
+            let mut keys: Vec<f64> = triples.iter().map(key).filter(|v| v.is_finite()).collect();
+            keys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            keys.dedup();
+            keys
+        };
+        let (xs, ys) = (distinct(|t| t.0), distinct(|t| t.1));
+        let mut values = vec![vec![f64::NAN; xs.len()]; ys.len()];
+        for (x, y, v) in triples.iter().filter(|(x, y, _)| x.is_finite() && y.is_finite()) {
+            let (c, r) = match (
+                xs.binary_search_by(|k| k.partial_cmp(x).unwrap()),
+                ys.binary_search_by(|k| k.partial_cmp(y).unwrap()),
+            ) {
+                (Ok(c), Ok(r)) => (c, r),
+                _ => continue,
+            };
+            let cell = &mut values[r][c];
+            *cell = if cell.is_nan() { *v } else { *cell + v };
+        }
+        let labels = |keys: Vec<f64>| keys.into_iter().map(|k| k.to_string()).collect();
+        Self::new(labels(xs), labels(ys), values)
+    }
+
+    pub fn get(&self, column: usize, row: usize) -> Option<f64> {
+        self.values.get(row)?.get(column).cloned().filter(|v| v.is_finite())
+    }
+
+    /// Smallest and largest value, if any cell has one.
+    pub fn extent(&self) -> Option<(f64, f64)> {
+        let (lo, hi) = self
+            .values
+            .iter()
+            .flatten()
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+        match lo <= hi {
+            true => Some((lo, hi)),
+            false => None,
+        }
+    }
+}
+
+/// How cell values map onto colors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorScale {
+    /// From the first stop at the low end to the last at the high end
+    Sequential(Vec<String>),
+    /// The middle stop sits at `center`, with each half stretched to its end
+    /// of the domain
+    Diverging { stops: Vec<String>, center: f64 },
+}
+
+impl ColorScale {
+    pub fn sequential() -> Self {
+        ColorScale::Sequential(VIRIDIS.iter().map(|c| c.to_string()).collect())
+    }
+
+    pub fn diverging(center: f64) -> Self {
+        ColorScale::Diverging {
+            stops: BLUE_RED.iter().map(|c| c.to_string()).collect(),
+            center,
+        }
+    }
+
+    pub fn stops(&self) -> &[String] {
+        match self {
+            ColorScale::Sequential(stops) | ColorScale::Diverging { stops, .. } => stops,
+        }
+    }
+
+    /// Where `value` falls along the stops, 0 to 1.
+    pub fn position(&self, value: f64, (lo, hi): (f64, f64)) -> f64 {
+        let t = |v: f64, lo: f64, hi: f64| match hi > lo {
+            true => ((v - lo) / (hi - lo)).max(0.0).min(1.0),
+            false => 0.5,
+        };
+        match self {
+            ColorScale::Sequential(_) => t(value, lo, hi),
+            ColorScale::Diverging { center, .. } if value < *center => 0.5 * t(value, lo, *center),
+            ColorScale::Diverging { center, .. } => 0.5 + 0.5 * t(value, *center, hi),
+        }
+    }
+
+    /// The domain to use when none is given: the grid's extent, made
+    /// symmetric around the center when diverging.
+    pub fn fit(&self, (lo, hi): (f64, f64)) -> (f64, f64) {
+        match self {
+            ColorScale::Sequential(_) => (lo, hi),
+            ColorScale::Diverging { center, .. } => {
+                let reach = (lo - center).abs().max((hi - center).abs());
+                (center - reach, center + reach)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Render {
+    /// `Bitmap` for grids of more than a few thousand cells, `Rects` otherwise
+    Auto,
+    /// One `<rect>` per cell
+    Rects,
+    /// One png image through `VTagWrapper::blit_bitmap`
+    Bitmap,
+}
+
+/// Pixel geometry of the plot area.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+    columns: usize,
+    rows: usize,
+}
+
+impl Frame {
+    fn cell_size(&self) -> (f32, f32) {
+        (self.width / self.columns.max(1) as f32, self.height / self.rows.max(1) as f32)
+    }
+
+    /// Top left corner of a cell.
+    fn cell_origin(&self, column: usize, row: usize) -> (f32, f32) {
+        let (cw, ch) = self.cell_size();
+        (self.left + cw * column as f32, self.top + ch * (self.rows - 1 - row) as f32)
+    }
+
+    fn cell_at(&self, (x, y): (f32, f32)) -> Option<(usize, usize)> {
+        if self.columns == 0 || self.rows == 0 {
+            return None;
+        }
+        if x < self.left || y < self.top || x >= self.left + self.width || y >= self.top + self.height {
+            return None;
+        }
+        let (cw, ch) = self.cell_size();
+        let column = ((x - self.left) / cw) as usize;
+        let from_top = ((y - self.top) / ch) as usize;
+        Some((column.min(self.columns - 1), self.rows - 1 - from_top.min(self.rows - 1)))
+    }
+}
+
+pub struct Heatmap {
+    props: Props,
+    domain: (f64, f64),
+    cells: Html,
+    hovered: Option<(usize, usize)>,
+    node_ref: NodeRef,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    MouseMove(yew::MouseEvent),
+    MouseLeave,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    /// Values by column and row
+    pub grid: Rc<Grid>,
+    #[prop_or_else(ColorScale::sequential)]
+    pub scale: ColorScale,
+    /// Values mapped onto the ends of the scale; fitted to the grid when unset
+    #[prop_or_default]
+    pub domain: Option<(f64, f64)>,
+    #[prop_or(Render::Auto)]
+    pub render: Render,
+    /// Title of the color bar
+    #[prop_or("value".to_string())]
+    pub label: String,
+    /// Called with the `(column, row)` under the pointer
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<(usize, usize)>>>,
+}
+
+impl Heatmap {
+    fn frame(&self) -> Frame {
+        let (top, right, bottom, left) = MARGINS;
+        Frame {
+            left: left as f32,
+            top: top as f32,
+            width: (self.props.width - left - right) as f32,
+            height: (self.props.height - top - bottom) as f32,
+            columns: self.props.grid.columns.len(),
+            rows: self.props.grid.rows.len(),
+        }
+    }
+
+    fn color(&self, value: f64) -> String {
+        series::ramp(self.props.scale.stops(), self.props.scale.position(value, self.domain) as f32)
+    }
+
+    fn rebuild(&mut self) {
+        let grid = &self.props.grid;
+        self.domain = self
+            .props
+            .domain
+            .or_else(|| grid.extent().map(|d| self.props.scale.fit(d)))
+            .unwrap_or((0.0, 1.0));
+        let count = grid.columns.len() * grid.rows.len();
+        let bitmap = match self.props.render {
+            Render::Auto => count > BITMAP_CELLS,
+            Render::Rects => false,
+            Render::Bitmap => true,
+        };
+        self.cells = match (count, bitmap) {
+            (0, _) => html! {},
+            (_, true) => self.bitmap_view(),
+            (_, false) => self.rects_view(),
+        };
+        self.hovered = None;
+    }
+
+    fn rects_view(&self) -> Html {
+        let frame = self.frame();
+        let (cw, ch) = frame.cell_size();
+        let grid = &self.props.grid;
+        let rects = (0..frame.rows).flat_map(|r| {
+            (0..frame.columns).filter_map(move |c| {
+                let value = grid.get(c, r)?;
+                let (x, y) = frame.cell_origin(c, r);
+                Some(html! {
+                    <rect x=x y=y width=cw height=ch fill=self.color(value) />
+                })
+            })
+        });
+        html! {
+            <g class="cells" shape-rendering="crispEdges">{ for rects }</g>
+        }
+    }
+
+    /// The grid drawn at one pixel per svg unit of the plot area, so the
+    /// browser only has a single image to deal with.
+    fn bitmap_view(&self) -> Html {
+        let frame = self.frame();
+        let (w, h) = (frame.width.max(1.0) as u32, frame.height.max(1.0) as u32);
+        let stops = self.props.scale.stops();
+        let lut: Vec<[u8; 3]> = (0..LUT_SIZE)
+            .map(|i| series::ramp_rgb(stops, i as f32 / (LUT_SIZE - 1) as f32))
+            .collect();
+        let grid = &self.props.grid;
+        let mut pixels = vec![255u8; (w * h * 3) as usize];
+        for py in 0..h {
+            let row = frame.rows - 1 - (py as usize * frame.rows / h as usize);
+            for px in 0..w {
+                let column = px as usize * frame.columns / w as usize;
+                let value = match grid.get(column, row) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let t = self.props.scale.position(value, self.domain);
+                let rgb = lut[(t * (LUT_SIZE - 1) as f64).round() as usize];
+                let at = ((py * w + px) * 3) as usize;
+                pixels[at..at + 3].copy_from_slice(&rgb);
+            }
+        }
+        let mut group = VTag::new("g");
+        group.add_attribute("class", &"cells");
+        {
+            let mut wrapper = VTagWrapper::new(&mut group, w, h);
+            let origin = (frame.left as i32, frame.top as i32);
+            if let Err(e) = wrapper.blit_bitmap(origin, (w, h), &pixels) {
+                ConsoleService::error(&format!("failed to draw heatmap: {:?}", e));
+            }
+        }
+        group.into()
+    }
+
+    /// Axes drawn here rather than by the charts crate, whose band axes
+    /// label every band however many there are.
+    fn axes_view(&self) -> Html {
+        let frame = self.frame();
+        let grid = &self.props.grid;
+        let (cw, ch) = frame.cell_size();
+        let (bottom, right) = (frame.top + frame.height, frame.left + frame.width);
+        let every = |n: usize| ((n + MAX_TICKS - 1) / MAX_TICKS).max(1);
+        let columns = grid
+            .columns
+            .iter()
+            .enumerate()
+            .step_by(every(grid.columns.len()))
+            .map(|(c, label)| {
+                let x = frame.left + cw * (c as f32 + 0.5);
+                html! {
+                    <g class="tick">
+                        <line x1=x y1=bottom x2=x y2=bottom + 6.0 stroke="#000000" />
+                        <text x=x y=bottom + 18.0 text-anchor="middle">{label}</text>
+                    </g>
+                }
+            });
+        let rows = grid
+            .rows
+            .iter()
+            .enumerate()
+            .step_by(every(grid.rows.len()))
+            .map(|(r, label)| {
+                let y = frame.top + ch * ((frame.rows - 1 - r) as f32 + 0.5);
+                html! {
+                    <g class="tick">
+                        <line x1=frame.left - 6.0 y1=y x2=frame.left y2=y stroke="#000000" />
+                        <text x=frame.left - 9.0 y=y + 4.0 text-anchor="end">{label}</text>
+                    </g>
+                }
+            });
+        html! {
+            <g class="axes" font-family="sans-serif" font-size=10>
+                <line x1=frame.left y1=bottom x2=right y2=bottom stroke="#000000" />
+                <line x1=frame.left y1=frame.top x2=frame.left y2=bottom stroke="#000000" />
+                { for columns }
+                { for rows }
+            </g>
+        }
+    }
+
+    fn color_bar_view(&self) -> Html {
+        let (lo, hi) = self.domain;
+        let stops = self.props.scale.stops();
+        let colors = (0..COLOR_BAR_STEPS)
+            .map(|i| series::ramp(stops, i as f32 / (COLOR_BAR_STEPS - 1) as f32))
+            .collect();
+        let center = match &self.props.scale {
+            ColorScale::Diverging { center, .. } => Some(format!("{}", center)),
+            ColorScale::Sequential(_) => None,
+        };
+        let labels = (format!("{}", lo), center, format!("{}", hi));
+        render::color_bar(self.props.width, &self.props.label, colors, labels)
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let (c, r) = match self.hovered {
+            Some(cell) => cell,
+            None => return html! {},
+        };
+        let frame = self.frame();
+        let (cw, ch) = frame.cell_size();
+        let (x, y) = frame.cell_origin(c, r);
+        let grid = &self.props.grid;
+        let value = match grid.get(c, r) {
+            Some(value) => value.to_string(),
+            None => "no value".to_string(),
+        };
+        let text = format!("{}, {}: {}", grid.columns[c], grid.rows[r], value);
+        html! {
+            <g pointer-events="none">
+                <rect class="hovered" x=x y=y width=cw height=ch fill="none" stroke="#333333" />
+                {render::tooltip_text(self.props.width, text)}
+            </g>
+        }
+    }
+}
+
+impl Component for Heatmap {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut heatmap = Self {
+            props,
+            domain: (0.0, 1.0),
+            cells: html! {},
+            hovered: None,
+            node_ref: NodeRef::default(),
+            link,
+        };
+        heatmap.rebuild();
+        heatmap
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let hovered = match msg {
+            Msg::MouseMove(e) => {
+                let view_box = (0.0, 0.0, self.props.width as f32, self.props.height as f32);
+                view::client_to_svg(&self.node_ref, view_box, (e.client_x(), e.client_y()))
+                    .and_then(|p| self.frame().cell_at(p))
+            }
+            Msg::MouseLeave => None,
+        };
+        if hovered == self.hovered {
+            return false;
+        }
+        self.hovered = hovered;
+        if let Some(cb) = &self.props.on_hover {
+            cb.emit(hovered);
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let mut root = render::svg_root(self.props.width, self.props.height);
+        root.node_ref = self.node_ref.clone();
+        // cells have no listeners of their own, which bitmaps couldn't have anyway
+        root.add_listener(Rc::new(yew::html::onmousemove::Wrapper::new(self.link.callback(Msg::MouseMove))));
+        root.add_listener(Rc::new(yew::html::onmouseleave::Wrapper::new(self.link.callback(|_| Msg::MouseLeave))));
+        root.add_child(self.cells.clone());
+        root.add_child(self.axes_view());
+        root.add_child(self.color_bar_view());
+        root.add_child(self.tooltip_view());
+        root.into()
+    }
+}
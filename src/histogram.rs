@@ -0,0 +1,420 @@
+use std::rc::Rc;
+use yew::prelude::*;
+
+use crate::chart::MARGINS;
+use crate::render;
+use crate::series;
+use crate::stats::{iqr, sorted, std_dev};
+use crate::view::{Domain, Projection};
+
+// more bins than this can't be told apart on screen anyway
+const MAX_BINS: usize = 1000;
+// how many points the density curve is sampled at
+const KDE_POINTS: usize = 200;
+
+/// Bottom of the log10 count axis, below 1 so single-sample bins still show.
+pub const LOG_FLOOR: f32 = -0.5;
+
+/// How samples are split into bins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Binning {
+    /// `log2(n) + 1` bins; fine for small, roughly normal samples
+    Sturges,
+    /// Width from the standard deviation
+    Scott,
+    /// Width from the interquartile range, so long tails don't widen the bins
+    FreedmanDiaconis,
+    /// Bins of this width, aligned to its multiples
+    Width(f64),
+    /// This many bins spanning the samples
+    Count(usize),
+}
+
+/// Samples from `start` (inclusive) to `end` (exclusive, except for the last bin).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+/// Bins the finite `samples`. Rules that give no usable width, e.g. Scott's
+/// for identical samples, fall back to Sturges.
+pub fn bins(samples: &[f64], binning: Binning) -> Vec<Bin> {
+    let sorted = sorted(samples);
+    let (lo, hi) = match (sorted.first(), sorted.last()) {
+        (Some(lo), Some(hi)) => (*lo, *hi),
+        _ => return Vec::new(),
+    };
+    if lo == hi {
+        return vec![Bin {
+            start: lo - 0.5,
+            end: hi + 0.5,
+            count: sorted.len(),
+        }];
+    }
+    let n = sorted.len() as f64;
+    let sturges = (hi - lo) / (n.log2().ceil() + 1.0);
+    let width = match binning {
+        Binning::Sturges => sturges,
+        Binning::Scott => 3.49 * std_dev(&sorted) / n.cbrt(),
+        Binning::FreedmanDiaconis => iqr(&sorted).map_or(f64::NAN, |iqr| 2.0 * iqr / n.cbrt()),
+        Binning::Width(width) => width,
+        Binning::Count(count) => (hi - lo) / count.max(1) as f64,
+    };
+    let width = match width.is_finite() && width > 0.0 {
+        true => width.max((hi - lo) / MAX_BINS as f64),
+        false => sturges,
+    };
+    let start = match binning {
+        Binning::Width(_) => (lo / width).floor() * width,
+        _ => lo,
+    };
+    let count = (((hi - start) / width).ceil() as usize).clamp(1, MAX_BINS);
+    let mut bins: Vec<Bin> = (0..count)
+        .map(|i| Bin {
+            start: start + width * i as f64,
+            end: start + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+    for v in sorted {
+        let idx = (((v - start) / width).floor() as usize).min(count - 1);
+        bins[idx].count += 1;
+    }
+    // rounding can leave an empty bin past the top sample
+    if bins.len() > 1 && bins[count - 1].count == 0 {
+        bins.pop();
+    }
+    bins
+}
+
+/// Silverman's rule of thumb for a Gaussian kernel's bandwidth.
+pub fn silverman(samples: &[f64]) -> f64 {
+    let sorted = sorted(samples);
+    if sorted.len() < 2 {
+        return 1.0;
+    }
+    let iqr = iqr(&sorted).unwrap_or(0.0);
+    let spread = match iqr > 0.0 {
+        true => std_dev(&sorted).min(iqr / 1.34),
+        false => std_dev(&sorted),
+    };
+    match spread > 0.0 {
+        true => 0.9 * spread * (sorted.len() as f64).powf(-0.2),
+        false => 1.0,
+    }
+}
+
+/// Gaussian kernel density of `samples` at each of `xs`.
+pub fn kde(samples: &[f64], bandwidth: f64, xs: &[f64]) -> Vec<f64> {
+    let samples: Vec<f64> = samples.iter().cloned().filter(|v| v.is_finite()).collect();
+    let norm = 1.0 / (samples.len() as f64 * bandwidth * (2.0 * std::f64::consts::PI).sqrt());
+    xs.iter()
+        .map(|x| {
+            let sum: f64 = samples.iter().map(|s| (-0.5 * ((x - s) / bandwidth).powi(2)).exp()).sum();
+            sum * norm
+        })
+        .collect()
+}
+
+/// Bar heights for `bins`: their counts, or running totals when `cumulative`.
+pub fn heights(bins: &[Bin], cumulative: bool) -> Vec<f64> {
+    let mut total = 0.0;
+    bins.iter()
+        .map(|b| match cumulative {
+            true => {
+                total += b.count as f64;
+                total
+            }
+            false => b.count as f64,
+        })
+        .collect()
+}
+
+/// The kernel density of `samples` across `bins` in the bars' units: counts
+/// per bin, or its running integral in samples when `cumulative`.
+pub fn density(samples: &[f64], bins: &[Bin], bandwidth: f64, cumulative: bool) -> Vec<(f64, f64)> {
+    let (lo, hi, width) = match (bins.first(), bins.last()) {
+        (Some(first), Some(last)) => (first.start, last.end, first.end - first.start),
+        _ => return Vec::new(),
+    };
+    let step = (hi - lo) / (KDE_POINTS - 1) as f64;
+    let xs: Vec<f64> = (0..KDE_POINTS).map(|i| lo + step * i as f64).collect();
+    let n = samples.iter().filter(|v| v.is_finite()).count() as f64;
+    let ys = kde(samples, bandwidth, &xs);
+    match cumulative {
+        true => {
+            // trapezoids from the start of the first bin
+            let mut area = 0.0;
+            let mut prev: Option<f64> = None;
+            xs.into_iter()
+                .zip(ys)
+                .map(|(x, y)| {
+                    area += prev.map(|p| (p + y) / 2.0 * step).unwrap_or(0.0);
+                    prev = Some(y);
+                    (x, area * n)
+                })
+                .collect()
+        }
+        false => xs.into_iter().zip(ys).map(|(x, y)| (x, y * n * width)).collect(),
+    }
+}
+
+/// Where a height goes on a log10 count axis; zero and anything under
+/// `LOG_FLOOR` sit on the floor.
+pub fn log_height(v: f64) -> f32 {
+    (v.log10() as f32).max(LOG_FLOOR)
+}
+
+pub struct Histogram {
+    props: Props,
+    bins: Vec<Bin>,
+    /// Bar heights, running totals when cumulative
+    heights: Vec<f64>,
+    /// Density scaled to the bars' units
+    curve: Vec<(f64, f64)>,
+    axes: Option<svg::node::element::Group>,
+    hovered: Option<usize>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    Hover(Option<usize>),
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    /// Values to bin; non-finite ones are skipped
+    pub samples: Rc<Vec<f64>>,
+    #[prop_or(Binning::Sturges)]
+    pub binning: Binning,
+    /// Each bar counts every sample up to its end
+    #[prop_or_default]
+    pub cumulative: bool,
+    /// Counts on a log10 axis starting at `LOG_FLOOR`; empty bins are left out
+    #[prop_or_default]
+    pub log_counts: bool,
+    /// Overlay a kernel density estimate scaled to the counts
+    #[prop_or_default]
+    pub kde: bool,
+    /// Kernel bandwidth; Silverman's rule when unset
+    #[prop_or_default]
+    pub bandwidth: Option<f64>,
+    /// Hex color of the bars; the first `PALETTE` color when unset
+    #[prop_or_default]
+    pub color: Option<String>,
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<Bin>>>,
+}
+
+impl Histogram {
+    fn rebuild(&mut self) {
+        let samples = &self.props.samples;
+        self.bins = bins(samples, self.props.binning);
+        self.heights = heights(&self.bins, self.props.cumulative);
+        self.curve = match self.props.kde {
+            true => {
+                let bandwidth = self
+                    .props
+                    .bandwidth
+                    .filter(|b| *b > 0.0)
+                    .unwrap_or_else(|| silverman(samples));
+                density(samples, &self.bins, bandwidth, self.props.cumulative)
+            }
+            false => Vec::new(),
+        };
+        self.hovered = None;
+        self.axes = Some(self.axes());
+    }
+
+    /// Where a height goes on the y axis, which is in log10 units for `log_counts`.
+    fn y_value(&self, v: f64) -> f32 {
+        match self.props.log_counts {
+            true => log_height(v),
+            false => v as f32,
+        }
+    }
+
+    fn projection(&self) -> Projection {
+        let (top, right, bottom, left) = MARGINS;
+        let x = match (self.bins.first(), self.bins.last()) {
+            (Some(first), Some(last)) => (first.start as f32, last.end as f32),
+            _ => (0.0, 1.0),
+        };
+        let highest = self
+            .heights
+            .iter()
+            .chain(self.curve.iter().map(|(_, y)| y))
+            .fold(0.0f64, |hi, v| hi.max(*v));
+        let y = match self.props.log_counts {
+            true => (LOG_FLOOR, self.y_value(highest).ceil().max(1.0)),
+            false => (0.0, highest.max(1.0) as f32),
+        };
+        Projection {
+            left: left as f32,
+            top: top as f32,
+            width: (self.props.width - left - right) as f32,
+            height: (self.props.height - top - bottom) as f32,
+            domain: Domain::new(x, y),
+        }
+    }
+
+    /// Axes drawn by the charts crate, except the log axis which it can't do.
+    fn axes(&self) -> svg::node::element::Group {
+        let (top, right, bottom, left) = MARGINS;
+        let (width, height) = (self.props.width, self.props.height);
+        let d = self.projection().domain;
+        let x = charts::ScaleLinear::new()
+            .set_domain(vec![d.x.0, d.x.1])
+            .set_range(vec![0, width - left - right]);
+        let y = charts::ScaleLinear::new()
+            .set_domain(vec![d.y.0, d.y.1])
+            .set_range(vec![height - top - bottom, 0]);
+        let c = charts::Chart::new()
+            .set_width(width)
+            .set_height(height)
+            .set_margins(top, right, bottom, left)
+            .add_axis_bottom(&x);
+        let c = match self.props.log_counts {
+            true => c,
+            false => c.add_axis_left(&y),
+        };
+        c.to_svg().unwrap()
+    }
+
+    fn log_axis_view(&self) -> Html {
+        if !self.props.log_counts {
+            return html! {};
+        }
+        let proj = self.projection();
+        let x = proj.left;
+        let ticks = (0..=proj.domain.y.1 as i32).map(|k| {
+            let (_, y) = proj.to_pixel((proj.domain.x.0, k as f32));
+            html! {
+                <g class="tick">
+                    <line x1=x - 6.0 y1=y x2=x y2=y stroke="#000000" />
+                    <text x=x - 9.0 y=y + 4.0 text-anchor="end" font-family="sans-serif" font-size=10>
+                        {format!("{}", 10f64.powi(k))}
+                    </text>
+                </g>
+            }
+        });
+        html! {
+            <g class="axis log-axis">
+                <line x1=x y1=proj.top x2=x y2=proj.top + proj.height stroke="#000000" />
+                { for ticks }
+            </g>
+        }
+    }
+
+    fn bars_view(&self) -> Html {
+        let proj = self.projection();
+        let color = series::palette_color(self.props.color.as_deref(), 0);
+        let bars = self
+            .bins
+            .iter()
+            .zip(&self.heights)
+            .enumerate()
+            .filter(|(_, (_, h))| **h > 0.0)
+            .map(|(idx, (bin, h))| {
+                let (x0, y) = proj.to_pixel((bin.start as f32, self.y_value(*h)));
+                let (x1, base) = proj.to_pixel((bin.end as f32, proj.domain.y.0));
+                let hovered = self.hovered == Some(idx);
+                html! {
+                    <rect
+                        class="bin"
+                        x=x0 y=y width=(x1 - x0).max(0.0) height=(base - y).max(0.0)
+                        fill=&color
+                        stroke=if hovered { "#333333" } else { "#FFFFFF" }
+                        stroke-width=0.5
+                        onmouseenter=self.link.callback(move |_| Msg::Hover(Some(idx)))
+                        onmouseleave=self.link.callback(|_| Msg::Hover(None))
+                    />
+                }
+            });
+        html! {
+            <g class="bins">{ for bars }</g>
+        }
+    }
+
+    fn curve_view(&self) -> Html {
+        if self.curve.is_empty() {
+            return html! {};
+        }
+        let proj = self.projection();
+        let d = self
+            .curve
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| {
+                let (px, py) = proj.to_pixel((*x as f32, self.y_value(*y)));
+                format!("{}{:.1} {:.1}", if i == 0 { "M" } else { "L" }, px, py)
+            })
+            .collect::<Vec<_>>()
+            .join("");
+        html! {
+            <path class="kde" d=d fill="none" stroke="#333333" stroke-width=1.5 pointer-events="none" />
+        }
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let (bin, height) = match self.hovered.map(|i| (self.bins[i], self.heights[i])) {
+            Some(hovered) => hovered,
+            None => return html! {},
+        };
+        let text = match self.props.cumulative {
+            true => format!("{} to {}: {} ({} up to here)", bin.start, bin.end, bin.count, height),
+            false => format!("{} to {}: {}", bin.start, bin.end, bin.count),
+        };
+        render::tooltip_text(self.props.width, text)
+    }
+}
+
+impl Component for Histogram {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut histogram = Self {
+            props,
+            bins: Vec::new(),
+            heights: Vec::new(),
+            curve: Vec::new(),
+            axes: None,
+            hovered: None,
+            link,
+        };
+        histogram.rebuild();
+        histogram
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Hover(idx) => {
+                if idx == self.hovered {
+                    return false;
+                }
+                self.hovered = idx;
+                if let Some(cb) = &self.props.on_hover {
+                    cb.emit(idx.map(|i| self.bins[i]));
+                }
+            }
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let root = render::svg_root(self.props.width, self.props.height);
+        let mut node = render::svg_to_vtag(self.axes.as_ref().unwrap(), root, |_, _| {});
+        node.add_child(self.log_axis_view());
+        node.add_child(self.bars_view());
+        node.add_child(self.curve_view());
+        node.add_child(self.tooltip_view());
+        node.into()
+    }
+}
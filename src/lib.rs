@@ -1,10 +1,45 @@
-mod utils;
+#![recursion_limit = "512"]
+pub mod utils;
 use wasm_bindgen::prelude::*;
 use yew::prelude::*;
 use plotters::prelude::*;
 
+pub mod bar;
+pub mod boxplot;
+pub mod brush;
 pub mod chart;
+pub mod columnar;
+pub mod curve;
+pub mod fetch;
+pub mod gesture;
+pub mod heatmap;
+pub mod histogram;
+pub mod ohlc;
+pub mod plot;
+pub mod prometheus;
+pub mod render;
+pub mod scatter;
+pub mod pie;
+pub mod series;
+pub mod source;
+pub mod spec;
+pub mod stack;
+pub mod stats;
+pub mod stream;
+pub mod sync;
+pub mod table;
+pub mod timeseries;
+pub mod transform;
+pub mod uncertainty;
+pub mod view;
+pub use bar::BarChart;
+pub use boxplot::BoxPlot;
 pub use chart::ChartComponent;
+pub use heatmap::Heatmap;
+pub use histogram::Histogram;
+pub use pie::PieChart;
+pub use plot::PlotComponent;
+pub use scatter::ScatterChart;
 
 pub struct App {
     link: ComponentLink<Self>,
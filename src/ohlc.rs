@@ -0,0 +1,279 @@
+//! Candlestick and OHLC bar charts with a volume pane, drawn with plotters
+//! for a `PlotComponent`.
+use plotters::coord::Shift;
+use plotters::drawing::DrawingAreaErrorKind;
+use plotters::prelude::*;
+use std::rc::Rc;
+use yew::services::ConsoleService;
+
+use crate::plot::DrawFn;
+use crate::series::ramp_rgb;
+use crate::table::format_timestamp;
+use crate::utils::{self, VTagWrapper};
+use crate::view::{Domain, Projection};
+
+// share of a slot along x taken up by one candle
+const BODY_WIDTH: f64 = 0.7;
+// a spacing this many times the usual one is a non-trading period
+const GAP_FACTOR: f64 = 1.5;
+const LABEL_AREA: u32 = 60;
+
+/// One period of trading; `time` is its start in unix seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Candle {
+    pub time: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Candle {
+    pub fn new(time: f64, open: f64, high: f64, low: f64, close: f64) -> Self {
+        Self {
+            time,
+            open,
+            high,
+            low,
+            close,
+            volume: 0.0,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f64) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// True if the period closed at or above where it opened.
+    pub fn is_up(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Candles,
+    /// A high-low line with ticks for the open (left) and close (right)
+    Bars,
+}
+
+/// How periods without trading, such as nights and weekends, are shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gaps {
+    /// Leave empty space for them on a true time axis
+    Show,
+    /// Put candles side by side and mark where time was skipped
+    Collapse,
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub style: Style,
+    pub gaps: Gaps,
+    /// Hex colors such as `#2CA02C` for periods closing up and down
+    pub up_color: String,
+    pub down_color: String,
+    /// Fraction of the height given to the volume pane; 0 hides it
+    pub volume_share: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            style: Style::Candles,
+            gaps: Gaps::Show,
+            up_color: "#2CA02C".to_string(),
+            down_color: "#D62728".to_string(),
+            volume_share: 0.25,
+        }
+    }
+}
+
+/// The usual spacing between candles: the median of the positive steps in
+/// time, or a day if there are none.
+pub fn interval(candles: &[Candle]) -> f64 {
+    let mut steps: Vec<f64> = candles
+        .windows(2)
+        .map(|w| w[1].time - w[0].time)
+        .filter(|d| *d > 0.0)
+        .collect();
+    if steps.is_empty() {
+        return 86_400.0;
+    }
+    steps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    steps[steps.len() / 2]
+}
+
+/// Indices of the candles that follow a non-trading period.
+pub fn gaps(candles: &[Candle]) -> Vec<usize> {
+    let limit = interval(candles) * GAP_FACTOR;
+    (1..candles.len())
+        .filter(|&i| candles[i].time - candles[i - 1].time > limit)
+        .collect()
+}
+
+/// Where each candle goes along x: its time, or its index when gaps are
+/// collapsed.
+pub fn positions(candles: &[Candle], gaps: Gaps) -> Vec<f64> {
+    match gaps {
+        Gaps::Show => candles.iter().map(|c| c.time).collect(),
+        Gaps::Collapse => (0..candles.len()).map(|i| i as f64).collect(),
+    }
+}
+
+/// A `PlotComponent` draw function for `candles`, which should be sorted by
+/// time. The projection returned is that of the price pane.
+pub fn draw_fn(candles: Rc<Vec<Candle>>, options: Options) -> DrawFn {
+    Rc::new(move |root| match draw(&candles, &options, root) {
+        Ok(projection) => projection,
+        Err(e) => {
+            ConsoleService::error(&format!("failed to draw ohlc chart: {:?}", e));
+            None
+        }
+    })
+}
+
+fn draw(
+    candles: &[Candle],
+    options: &Options,
+    root: DrawingArea<VTagWrapper, Shift>,
+) -> Result<Option<Projection>, DrawingAreaErrorKind<utils::Error>> {
+    root.fill(&WHITE)?;
+    let candles: Vec<Candle> = candles
+        .iter()
+        .filter(|c| [c.time, c.open, c.high, c.low, c.close].iter().all(|v| v.is_finite()))
+        .cloned()
+        .collect();
+    if candles.is_empty() {
+        return Ok(None);
+    }
+
+    let xs = positions(&candles, options.gaps);
+    let slot = match options.gaps {
+        Gaps::Show => interval(&candles),
+        Gaps::Collapse => 1.0,
+    };
+    let x_range = (xs[0] - slot / 2.0)..(xs[xs.len() - 1] + slot / 2.0);
+    let (mut low, mut high) = candles
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), c| (lo.min(c.low), hi.max(c.high)));
+    let pad = if high > low { (high - low) * 0.05 } else { 1.0 };
+    low -= pad;
+    high += pad;
+
+    // labels are dates, with the time of day once candles are shorter than that
+    let with_time = interval(&candles) < 86_400.0;
+    let times: Vec<f64> = candles.iter().map(|c| c.time).collect();
+    let gaps_mode = options.gaps;
+    let label = move |x: &f64| match gaps_mode {
+        Gaps::Show => format_timestamp(*x, with_time),
+        Gaps::Collapse => {
+            let i = x.round().max(0.0) as usize;
+            times.get(i).map(|t| format_timestamp(*t, with_time)).unwrap_or_default()
+        }
+    };
+
+    let (_, height) = root.dim_in_pixel();
+    let share = options.volume_share.clamp(0.0, 0.9);
+    let volume_height = (height as f64 * share) as u32;
+    let (upper, lower) = root.split_vertically(height - volume_height);
+
+    let mut price = ChartBuilder::on(&upper)
+        .margin(5)
+        .y_label_area_size(LABEL_AREA)
+        .x_label_area_size(if volume_height > 0 { 0 } else { 30 })
+        .build_ranged(x_range.clone(), low..high)?;
+    {
+        let mut mesh = price.configure_mesh();
+        mesh.disable_x_mesh().y_label_formatter(&|v| format!("{:.2}", v));
+        if volume_height > 0 {
+            mesh.x_labels(0);
+        } else {
+            mesh.x_label_formatter(&label);
+        }
+        mesh.draw()?;
+    }
+
+    let (up_color, down_color) = (rgb(&options.up_color), rgb(&options.down_color));
+    let up = ShapeStyle::from(&up_color).filled();
+    let down = ShapeStyle::from(&down_color).filled();
+    let half = slot * BODY_WIDTH / 2.0;
+    match options.style {
+        Style::Candles => {
+            // candle widths are in pixels, so measure one slot on screen
+            let x0 = price.backend_coord(&(xs[0], low)).0;
+            let x1 = price.backend_coord(&(xs[0] + slot, low)).0;
+            let width = (((x1 - x0) as f64 * BODY_WIDTH) as u32).max(1);
+            price.draw_series(candles.iter().zip(&xs).map(|(c, x)| {
+                CandleStick::new(*x, c.open, c.high, c.low, c.close, up.clone(), down.clone(), width)
+            }))?;
+        }
+        Style::Bars => {
+            for (c, x) in candles.iter().zip(&xs) {
+                let color = if c.is_up() { &up_color } else { &down_color };
+                price.draw_series(vec![
+                    PathElement::new(vec![(*x, c.low), (*x, c.high)], color),
+                    PathElement::new(vec![(*x - half, c.open), (*x, c.open)], color),
+                    PathElement::new(vec![(*x, c.close), (*x + half, c.close)], color),
+                ])?;
+            }
+        }
+    }
+
+    // with gaps collapsed, a faint line stands in for each skipped period
+    if options.gaps == Gaps::Collapse {
+        let marker = RGBColor(0xBB, 0xBB, 0xBB);
+        price.draw_series(gaps(&candles).into_iter().map(|i| {
+            let x = i as f64 - 0.5;
+            PathElement::new(vec![(x, low), (x, high)], &marker)
+        }))?;
+    }
+
+    let (px, py) = price.plotting_area().get_pixel_range();
+    let domain = Domain::new(
+        (x_range.start as f32, x_range.end as f32),
+        (low as f32, high as f32),
+    );
+    let projection = Projection::from_pixel_range((px, py), domain);
+
+    if volume_height > 0 {
+        let max_volume = candles.iter().map(|c| c.volume).filter(|v| v.is_finite()).fold(0.0, f64::max);
+        let top = if max_volume > 0.0 { max_volume * 1.1 } else { 1.0 };
+        let mut volume = ChartBuilder::on(&lower)
+            .margin(5)
+            .margin_top(0)
+            .y_label_area_size(LABEL_AREA)
+            .x_label_area_size(30)
+            .build_ranged(x_range, 0.0..top)?;
+        volume
+            .configure_mesh()
+            .disable_x_mesh()
+            .y_labels(3)
+            .y_label_formatter(&|v| format_volume(*v))
+            .x_label_formatter(&label)
+            .draw()?;
+        volume.draw_series(candles.iter().zip(&xs).filter(|(c, _)| c.volume > 0.0).map(|(c, x)| {
+            let style = if c.is_up() { up.clone() } else { down.clone() };
+            Rectangle::new([(*x - half, 0.0), (*x + half, c.volume)], style)
+        }))?;
+    }
+
+    Ok(Some(projection))
+}
+
+fn format_volume(v: f64) -> String {
+    match v.abs() {
+        a if a >= 1e9 => format!("{:.1}B", v / 1e9),
+        a if a >= 1e6 => format!("{:.1}M", v / 1e6),
+        a if a >= 1e3 => format!("{:.1}K", v / 1e3),
+        _ => format!("{:.0}", v),
+    }
+}
+
+fn rgb(color: &str) -> RGBColor {
+    // a single stop ramps to itself
+    let [r, g, b] = ramp_rgb(&[color], 0.0);
+    RGBColor(r, g, b)
+}
@@ -0,0 +1,402 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+use svg::node::element::{Group, Path, Polyline, Text};
+use yew::prelude::*;
+
+use crate::chart::MARGINS;
+use crate::render;
+use crate::series;
+
+// room around the pie for outside labels, in svg units
+const LABEL_SPACE: f64 = 70.0;
+// smallest vertical gap between two outside labels
+const LABEL_GAP: f64 = 14.0;
+
+/// One part of the whole.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Slice {
+    pub label: String,
+    pub value: f64,
+    /// Hex color such as `#FF0000`; picked from `PALETTE` when unset
+    pub color: Option<String>,
+}
+
+impl Slice {
+    pub fn new<S: Into<String>>(label: S, value: f64) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            color: None,
+        }
+    }
+
+    pub fn with_color<S: Into<String>>(mut self, color: S) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// A slice as laid out around the circle. Angles are in radians, clockwise
+/// from 12 o'clock.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sector {
+    pub label: String,
+    pub value: f64,
+    /// Share of the total, 0 to 1
+    pub fraction: f64,
+    pub start: f64,
+    pub end: f64,
+    /// Indices of the slices it stands for; several for the "other" sector
+    pub slices: Vec<usize>,
+}
+
+impl Sector {
+    pub fn mid(&self) -> f64 {
+        (self.start + self.end) / 2.0
+    }
+}
+
+/// Lays `slices` out in order from `start` radians. Slices with no positive,
+/// finite value are left out. Those under `other_below` of the total are
+/// merged into one last sector called `other_label`, unless only one is.
+pub fn layout(slices: &[Slice], start: f64, other_below: Option<f64>, other_label: &str) -> Vec<Sector> {
+    let kept: Vec<usize> = (0..slices.len())
+        .filter(|i| slices[*i].value.is_finite() && slices[*i].value > 0.0)
+        .collect();
+    let total: f64 = kept.iter().map(|i| slices[*i].value).sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+    let threshold = other_below.unwrap_or(0.0) * total;
+    let (small, large): (Vec<usize>, Vec<usize>) = kept.iter().partition(|i| slices[**i].value < threshold);
+    let mut parts: Vec<(String, f64, Vec<usize>)> = match small.len() > 1 {
+        true => large
+            .into_iter()
+            .map(|i| (slices[i].label.clone(), slices[i].value, vec![i]))
+            .collect(),
+        false => kept
+            .iter()
+            .map(|i| (slices[*i].label.clone(), slices[*i].value, vec![*i]))
+            .collect(),
+    };
+    if small.len() > 1 {
+        let value = small.iter().map(|i| slices[*i].value).sum();
+        parts.push((other_label.to_string(), value, small));
+    }
+    let mut angle = start;
+    parts
+        .into_iter()
+        .map(|(label, value, slices)| {
+            let fraction = value / total;
+            let sector = Sector {
+                label,
+                value,
+                fraction,
+                start: angle,
+                end: angle + fraction * 2.0 * PI,
+                slices,
+            };
+            angle = sector.end;
+            sector
+        })
+        .collect()
+}
+
+fn polar((cx, cy): (f64, f64), r: f64, angle: f64) -> (f64, f64) {
+    (cx + r * angle.sin(), cy - r * angle.cos())
+}
+
+/// Path data for the ring between radii `inner` and `outer` from `start` to
+/// `end`; a wedge when `inner` is zero.
+pub fn sector_path(center: (f64, f64), inner: f64, outer: f64, start: f64, end: f64) -> String {
+    let sweep = end - start;
+    // an arc can't start and end at the same point, so a full ring is two halves
+    if sweep >= 2.0 * PI - 1e-9 {
+        let half = start + PI;
+        return format!(
+            "{}{}",
+            sector_path(center, inner, outer, start, half),
+            sector_path(center, inner, outer, half, start + 2.0 * PI)
+        );
+    }
+    let large = if sweep > PI { 1 } else { 0 };
+    let (x0, y0) = polar(center, outer, start);
+    let (x1, y1) = polar(center, outer, end);
+    let mut d = format!(
+        "M{:.2} {:.2}A{:.2} {:.2} 0 {} 1 {:.2} {:.2}",
+        x0, y0, outer, outer, large, x1, y1
+    );
+    if inner > 0.0 {
+        let (x2, y2) = polar(center, inner, end);
+        let (x3, y3) = polar(center, inner, start);
+        d += &format!(
+            "L{:.2} {:.2}A{:.2} {:.2} 0 {} 0 {:.2} {:.2}Z",
+            x2, y2, inner, inner, large, x3, y3
+        );
+    } else {
+        d += &format!("L{:.2} {:.2}Z", center.0, center.1);
+    }
+    d
+}
+
+/// Where an outside label goes: the elbow of its leader line and the text anchor.
+struct Placement {
+    sector: usize,
+    edge: (f64, f64),
+    elbow: (f64, f64),
+    right: bool,
+}
+
+/// Spreads the ascending label heights `ys` at least `gap` apart, keeping
+/// them half a gap inside `0..height`: first pushing down past the ones
+/// above, then back up from the bottom for those pushed out of the chart.
+pub fn spread_labels(ys: &mut [f64], gap: f64, height: f64) {
+    let (top, bottom) = (gap / 2.0, height - gap / 2.0);
+    for i in 0..ys.len() {
+        let min = if i == 0 { top } else { ys[i - 1] + gap };
+        ys[i] = ys[i].max(min);
+    }
+    for i in (0..ys.len()).rev() {
+        let max = if i + 1 == ys.len() { bottom } else { ys[i + 1] - gap };
+        ys[i] = ys[i].min(max);
+    }
+}
+
+/// Places labels beside the pie, pushing them apart on each side so they
+/// don't overlap or leave a chart `height` units tall.
+fn place_labels(sectors: &[Sector], center: (f64, f64), outer: f64, height: f64) -> Vec<Placement> {
+    let mut placements: Vec<Placement> = sectors
+        .iter()
+        .enumerate()
+        .map(|(i, s)| Placement {
+            sector: i,
+            edge: polar(center, outer, s.mid()),
+            elbow: polar(center, outer + 14.0, s.mid()),
+            right: s.mid().rem_euclid(2.0 * PI) < PI,
+        })
+        .collect();
+    for right in [true, false].iter() {
+        let mut side: Vec<&mut Placement> = placements.iter_mut().filter(|p| p.right == *right).collect();
+        side.sort_by(|a, b| a.elbow.1.partial_cmp(&b.elbow.1).unwrap());
+        let mut ys: Vec<f64> = side.iter().map(|p| p.elbow.1).collect();
+        spread_labels(&mut ys, LABEL_GAP, height);
+        for (p, y) in side.iter_mut().zip(ys) {
+            p.elbow.1 = y;
+        }
+    }
+    placements
+}
+
+pub struct PieChart {
+    props: Props,
+    sectors: Vec<Sector>,
+    group: Option<Group>,
+    hovered: Option<usize>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    Hover(Option<usize>),
+    Click(usize),
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    pub slices: Vec<Slice>,
+    /// Radius of the hole as a fraction of the outer radius; 0 for a pie
+    #[prop_or(0.0)]
+    pub inner_radius: f64,
+    /// Where the first slice starts, in degrees clockwise from 12 o'clock
+    #[prop_or(0.0)]
+    pub start_angle: f64,
+    /// Gap between slices, in degrees
+    #[prop_or(0.0)]
+    pub pad_angle: f64,
+    /// Slices under this share of the total are merged into one
+    #[prop_or_default]
+    pub other_threshold: Option<f64>,
+    #[prop_or("Other".to_string())]
+    pub other_label: String,
+    /// Color of the merged slice
+    #[prop_or("#AAAAAA".to_string())]
+    pub other_color: String,
+    /// Label each slice outside the pie with a leader line
+    #[prop_or(true)]
+    pub show_labels: bool,
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<Sector>>>,
+    #[prop_or_default]
+    pub on_click: Option<Callback<Sector>>,
+}
+
+impl PieChart {
+    fn center_and_radius(&self) -> ((f64, f64), f64) {
+        let (top, _, _, _) = MARGINS;
+        let (width, height) = (self.props.width as f64, self.props.height as f64);
+        // leave the top margin to the tooltip, as the other charts do
+        let center = (width / 2.0, (height + top as f64 / 2.0) / 2.0);
+        let space = if self.props.show_labels { LABEL_SPACE } else { 10.0 };
+        let outer = (width.min(height - top as f64 / 2.0) / 2.0 - space).max(1.0);
+        (center, outer)
+    }
+
+    fn color(&self, sector: &Sector) -> String {
+        match sector.slices.as_slice() {
+            [i] => series::palette_color(self.props.slices[*i].color.as_deref(), *i),
+            _ => self.props.other_color.clone(),
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.sectors = layout(
+            &self.props.slices,
+            self.props.start_angle.to_radians(),
+            self.props.other_threshold,
+            &self.props.other_label,
+        );
+        self.group = Some(self.build());
+    }
+
+    fn build(&self) -> Group {
+        let (center, outer) = self.center_and_radius();
+        let inner = outer * self.props.inner_radius.clamp(0.0, 0.95);
+        let pad = self.props.pad_angle.max(0.0).to_radians() / 2.0;
+        let single = self.sectors.len() == 1;
+        let slices = self.sectors.iter().enumerate().fold(Group::new().set("class", "slices"), |g, (i, s)| {
+            // a lone slice is a full circle with nothing to pad against
+            let (start, end) = match single {
+                true => (s.start, s.end),
+                false => {
+                    let pad = pad.min((s.end - s.start) / 2.0);
+                    (s.start + pad, s.end - pad)
+                }
+            };
+            let mut color = self.color(s);
+            if self.hovered.map(|h| h != i).unwrap_or(false) {
+                color = series::dim(&color);
+            }
+            let path = Path::new()
+                .set("d", sector_path(center, inner, outer, start, end))
+                .set("fill", color)
+                .set("stroke", "#FFFFFF");
+            // wrapped in a group so the listeners have somewhere to go
+            g.add(
+                Group::new()
+                    .set("class", "slice")
+                    .set("data-sector", i)
+                    .set("style", "cursor: pointer")
+                    .add(path),
+            )
+        });
+        let group = Group::new().add(slices);
+        match self.props.show_labels {
+            true => group.add(self.labels(center, outer)),
+            false => group,
+        }
+    }
+
+    fn labels(&self, center: (f64, f64), outer: f64) -> Group {
+        place_labels(&self.sectors, center, outer, self.props.height as f64)
+            .into_iter()
+            .fold(Group::new().set("class", "labels"), |g, p| {
+                let s = &self.sectors[p.sector];
+                let end_x = match p.right {
+                    true => center.0 + outer + 24.0,
+                    false => center.0 - outer - 24.0,
+                };
+                let points = format!(
+                    "{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}",
+                    p.edge.0, p.edge.1, p.elbow.0, p.elbow.1, end_x, p.elbow.1
+                );
+                let line = Polyline::new()
+                    .set("points", points)
+                    .set("fill", "none")
+                    .set("stroke", "#888888");
+                let text = Text::new()
+                    .set("x", end_x + if p.right { 4.0 } else { -4.0 })
+                    .set("y", p.elbow.1 + 4.0)
+                    .set("text-anchor", if p.right { "start" } else { "end" })
+                    .set("font-family", "sans-serif")
+                    .set("font-size", 11)
+                    .add(svg::node::Text::new(s.label.clone()));
+                g.add(line).add(text)
+            })
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let s = match self.hovered.and_then(|i| self.sectors.get(i)) {
+            Some(s) => s,
+            None => return html! {},
+        };
+        let text = format!("{}: {} ({:.1}%)", s.label, s.value, s.fraction * 100.0);
+        render::tooltip_text(self.props.width, text)
+    }
+}
+
+impl Component for PieChart {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut chart = Self {
+            props,
+            sectors: Vec::new(),
+            group: None,
+            hovered: None,
+            link,
+        };
+        chart.rebuild();
+        chart
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Hover(idx) => {
+                if idx == self.hovered {
+                    return false;
+                }
+                self.hovered = idx;
+                if let Some(cb) = &self.props.on_hover {
+                    cb.emit(idx.and_then(|i| self.sectors.get(i).cloned()));
+                }
+                self.group = Some(self.build());
+            }
+            Msg::Click(idx) => {
+                if let (Some(cb), Some(s)) = (&self.props.on_click, self.sectors.get(idx)) {
+                    cb.emit(s.clone());
+                }
+                return false;
+            }
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.hovered = None;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let mut root = render::svg_root(self.props.width, self.props.height);
+        // stretching would turn the circle into an ellipse
+        root.add_attribute("preserveAspectRatio", &"xMidYMid meet");
+        let link = &self.link;
+        let mut node = render::svg_to_vtag(self.group.as_ref().unwrap(), root, |attrs, vnode| {
+            let idx = match attrs.get("data-sector").and_then(|v| v.parse::<usize>().ok()) {
+                Some(idx) => idx,
+                None => return,
+            };
+            vnode.add_listener(Rc::new(yew::html::onmouseenter::Wrapper::new(
+                link.callback(move |_| Msg::Hover(Some(idx))),
+            )));
+            vnode.add_listener(Rc::new(yew::html::onmouseleave::Wrapper::new(
+                link.callback(|_| Msg::Hover(None)),
+            )));
+            vnode.add_listener(Rc::new(yew::html::onclick::Wrapper::new(
+                link.callback(move |_| Msg::Click(idx)),
+            )));
+        });
+        node.add_child(self.tooltip_view());
+        node.into()
+    }
+}
@@ -0,0 +1,145 @@
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew::virtual_dom::VTag;
+
+use crate::brush::{Brush, BrushMode, BrushSelection};
+use crate::utils::VTagWrapper;
+use crate::view::{self, Projection};
+
+/// Draws a plotters chart onto the component's svg. Returns the projection of
+/// the plotting area if the chart should take part in pointer interaction.
+pub type DrawFn = Rc<dyn for<'a> Fn(DrawingArea<VTagWrapper<'a>, Shift>) -> Option<Projection>>;
+
+/// Hosts a chart drawn with plotters through `VTagWrapper`.
+pub struct PlotComponent {
+    props: Props,
+    svg: VTag,
+    projection: Option<Projection>,
+    brush: Option<Brush>,
+    node_ref: NodeRef,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    DragStart(yew::MouseEvent),
+    DragMove(yew::MouseEvent),
+    DragEnd,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: u32,
+    pub height: u32,
+    pub draw: DrawFn,
+    /// Data positions used to report which points a brush selected
+    #[prop_or_default]
+    pub points: Vec<(f32, f32)>,
+    #[prop_or_default]
+    pub brush: Option<BrushMode>,
+    #[prop_or_default]
+    pub on_brush: Option<Callback<BrushSelection>>,
+}
+
+impl PlotComponent {
+    fn render(props: &Props) -> (VTag, Option<Projection>) {
+        let (width, height) = (props.width, props.height);
+        let mut svg = VTag::new("svg");
+        svg.add_attribute("width", &"100%");
+        svg.add_attribute("height", &"100%");
+        svg.add_attribute("viewBox", &format!("0 0 {} {}", width, height));
+        svg.add_attribute("preserveAspectRatio", &"none");
+        let projection = {
+            let wrapper = VTagWrapper::new(&mut svg, width, height);
+            (props.draw)(wrapper.into_drawing_area())
+        };
+        (svg, projection)
+    }
+
+    fn svg_point(&self, e: &yew::MouseEvent) -> Option<(f32, f32)> {
+        let view_box = (0.0, 0.0, self.props.width as f32, self.props.height as f32);
+        view::client_to_svg(&self.node_ref, view_box, (e.client_x(), e.client_y()))
+    }
+}
+
+impl Component for PlotComponent {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let (svg, projection) = Self::render(&props);
+        Self {
+            brush: props.brush.map(Brush::new),
+            props,
+            svg,
+            projection,
+            node_ref: NodeRef::default(),
+            link,
+        }
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let proj = match self.projection {
+            Some(proj) => proj,
+            None => return false,
+        };
+        let p = match &msg {
+            Msg::DragStart(e) | Msg::DragMove(e) => self.svg_point(e),
+            Msg::DragEnd => None,
+        };
+        let brush = match &mut self.brush {
+            Some(brush) => brush,
+            None => return false,
+        };
+        match msg {
+            Msg::DragStart(_) => match p.filter(|p| proj.contains(*p)) {
+                Some(p) => {
+                    brush.start(p, &proj);
+                    true
+                }
+                None => false,
+            },
+            Msg::DragMove(_) => match p {
+                Some(p) => brush.drag(p, &proj),
+                None => false,
+            },
+            Msg::DragEnd => {
+                if !brush.is_dragging() {
+                    return false;
+                }
+                if let Some(domain) = brush.end(&proj) {
+                    let points = brush
+                        .select(&proj, self.props.points.iter().cloned())
+                        .into_iter()
+                        .map(|i| (0, i))
+                        .collect();
+                    if let Some(cb) = &self.props.on_brush {
+                        cb.emit(BrushSelection { domain, points });
+                    }
+                }
+                true
+            }
+        }
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props.brush != self.props.brush {
+            self.brush = props.brush.map(Brush::new);
+        }
+        let (svg, projection) = Self::render(&props);
+        self.svg = svg;
+        self.projection = projection;
+        self.props = props;
+        true
+    }
+    fn view(&self) -> Html {
+        let mut svg = self.svg.clone();
+        svg.node_ref = self.node_ref.clone();
+        svg.add_listener(Rc::new(yew::html::onmousedown::Wrapper::new(self.link.callback(Msg::DragStart))));
+        svg.add_listener(Rc::new(yew::html::onmousemove::Wrapper::new(self.link.callback(Msg::DragMove))));
+        svg.add_listener(Rc::new(yew::html::onmouseup::Wrapper::new(self.link.callback(|_| Msg::DragEnd))));
+        svg.add_listener(Rc::new(yew::html::onmouseleave::Wrapper::new(self.link.callback(|_| Msg::DragEnd))));
+        if let (Some(brush), Some(proj)) = (&self.brush, &self.projection) {
+            svg.add_child(brush.view(proj));
+        }
+        svg.into()
+    }
+}
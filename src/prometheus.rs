@@ -0,0 +1,414 @@
+use serde::Deserialize;
+use std::rc::Rc;
+use thiserror::Error;
+
+use crate::fetch::MapFn;
+use crate::series::Series;
+use crate::stream::StreamHandle;
+
+#[derive(Error, Debug)]
+pub enum PromError {
+    #[error("line {line}: {reason}")]
+    Line { line: usize, reason: String },
+    #[error("invalid query response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("query failed ({kind}): {message}")]
+    Query { kind: String, message: String },
+    #[error("unsupported result type `{0}`")]
+    ResultType(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    GaugeHistogram,
+    Summary,
+    Info,
+    StateSet,
+    Unknown,
+}
+
+impl MetricType {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "counter" => MetricType::Counter,
+            "gauge" => MetricType::Gauge,
+            "histogram" => MetricType::Histogram,
+            "gaugehistogram" => MetricType::GaugeHistogram,
+            "summary" => MetricType::Summary,
+            "info" => MetricType::Info,
+            "stateset" => MetricType::StateSet,
+            _ => MetricType::Unknown,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// The sample's own name, e.g. `http_duration_seconds_bucket`
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+    /// Unix seconds, when the exposition gives one
+    pub timestamp: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricFamily {
+    pub name: String,
+    pub kind: MetricType,
+    pub help: String,
+    pub samples: Vec<Sample>,
+}
+
+/// Names series from their labels. `{{label}}` is replaced by the label's
+/// value and `{{__name__}}` by the metric name, like Grafana's legend format.
+/// Without a template series are named `metric{label="value",...}`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NameTemplate(pub Option<String>);
+
+impl NameTemplate {
+    pub fn new<S: Into<String>>(template: S) -> Self {
+        Self(Some(template.into()))
+    }
+
+    pub fn render(&self, name: &str, labels: &[(String, String)]) -> String {
+        let template = match &self.0 {
+            Some(template) => template,
+            None => {
+                if labels.is_empty() {
+                    return name.to_string();
+                }
+                let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{}={:?}", k, v)).collect();
+                return format!("{}{{{}}}", name, labels.join(","));
+            }
+        };
+        let mut out = String::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let end = match rest[start..].find("}}") {
+                Some(end) => start + end,
+                None => break,
+            };
+            let key = rest[start + 2..end].trim();
+            match key {
+                "__name__" => out.push_str(name),
+                key => out.push_str(
+                    labels
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, v)| v.as_str())
+                        .unwrap_or(""),
+                ),
+            }
+            rest = &rest[end + 2..];
+        }
+        out.push_str(rest);
+        // missing labels shouldn't leave dangling separators behind
+        out.trim().to_string()
+    }
+}
+
+/// Parses the Prometheus text exposition format, or OpenMetrics when the
+/// input ends with `# EOF`. Timestamps are converted to unix seconds.
+pub fn parse_exposition(text: &str) -> Result<Vec<MetricFamily>, PromError> {
+    // prometheus timestamps are milliseconds, openmetrics ones seconds
+    let open_metrics = text.lines().any(|l| l.trim() == "# EOF");
+    let mut families: Vec<MetricFamily> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            let (keyword, name, rest) = (parts.next(), parts.next(), parts.next().unwrap_or(""));
+            let name = match (keyword, name) {
+                (Some("HELP"), Some(name)) | (Some("TYPE"), Some(name)) => name,
+                _ => continue,
+            };
+            let family = family_mut(&mut families, name);
+            match keyword {
+                Some("HELP") => family.help = unescape(rest),
+                _ => family.kind = MetricType::from_name(rest.trim()),
+            }
+            continue;
+        }
+        let sample = parse_sample(line_no, line, open_metrics)?;
+        let family = match families
+            .iter()
+            .rposition(|f| sample.name == f.name || sample_of(&sample.name, &f.name))
+        {
+            Some(idx) => &mut families[idx],
+            None => family_mut(&mut families, &sample.name.clone()),
+        };
+        family.samples.push(sample);
+    }
+    Ok(families)
+}
+
+fn family_mut<'a>(families: &'a mut Vec<MetricFamily>, name: &str) -> &'a mut MetricFamily {
+    let idx = match families.iter().position(|f| f.name == name) {
+        Some(idx) => idx,
+        None => {
+            families.push(MetricFamily {
+                name: name.to_string(),
+                kind: MetricType::Unknown,
+                help: String::new(),
+                samples: Vec::new(),
+            });
+            families.len() - 1
+        }
+    };
+    &mut families[idx]
+}
+
+// histogram, summary and counter samples carry a suffix on the family name
+fn sample_of(sample: &str, family: &str) -> bool {
+    sample
+        .strip_prefix(family)
+        .map(|suffix| ["_bucket", "_sum", "_count", "_total", "_created", "_gcount", "_gsum", "_info"].contains(&suffix))
+        .unwrap_or(false)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                out.push('\n');
+            }
+            ('\\', Some(e @ '\\')) | ('\\', Some(e @ '"')) => {
+                chars.next();
+                out.push(e);
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+fn parse_value(text: &str) -> Option<f64> {
+    match text {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        text => text.parse().ok(),
+    }
+}
+
+fn parse_sample(line: usize, text: &str, open_metrics: bool) -> Result<Sample, PromError> {
+    let err = |reason: &str| PromError::Line {
+        line,
+        reason: reason.to_string(),
+    };
+    let name_end = text
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or_else(|| err("missing value"))?;
+    let name = &text[..name_end];
+    if name.is_empty() {
+        return Err(err("missing metric name"));
+    }
+    let mut labels = Vec::new();
+    let mut rest = &text[name_end..];
+    if rest.starts_with('{') {
+        let mut chars = rest.char_indices().skip(1).peekable();
+        let close = loop {
+            while chars.peek().map(|(_, c)| c.is_whitespace() || *c == ',').unwrap_or(false) {
+                chars.next();
+            }
+            let (start, c) = chars.next().ok_or_else(|| err("unterminated label set"))?;
+            if c == '}' {
+                break start;
+            }
+            let mut end = start;
+            for (i, c) in chars.by_ref() {
+                if c == '=' {
+                    end = i;
+                    break;
+                }
+            }
+            if end == start {
+                return Err(err("label without a value"));
+            }
+            let key = rest[start..end].trim().to_string();
+            if chars.next().map(|(_, c)| c) != Some('"') {
+                return Err(err(&format!("value of label `{}` is not quoted", key)));
+            }
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, c)) => value.push(c),
+                        None => return Err(err("unterminated label value")),
+                    },
+                    Some((_, '"')) => break,
+                    Some((_, c)) => value.push(c),
+                    None => return Err(err("unterminated label value")),
+                }
+            }
+            labels.push((key, value));
+        };
+        rest = &rest[close + 1..];
+    }
+    // drop openmetrics exemplars, which follow the value; label values are behind us
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let mut parts = rest.split_whitespace();
+    let value = parts.next().ok_or_else(|| err("missing value"))?;
+    let value = parse_value(value).ok_or_else(|| err(&format!("`{}` is not a number", value)))?;
+    let timestamp = match parts.next() {
+        Some(ts) => {
+            let ts: f64 = ts.parse().map_err(|_| err(&format!("`{}` is not a timestamp", ts)))?;
+            Some(if open_metrics { ts } else { ts / 1000.0 })
+        }
+        None => None,
+    };
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+/// A sample value as a chart value. Series hold whole numbers, so values are
+/// multiplied by `scale` first to keep small metrics apart, e.g. 1000 to
+/// chart a latency in seconds as milliseconds.
+fn scaled(value: f64, scale: f64) -> Option<isize> {
+    let v = (value * scale).round();
+    match v.is_finite() && v >= isize::MIN as f64 && v < isize::MAX as f64 {
+        true => Some(v as isize),
+        false => None,
+    }
+}
+
+/// One point per sample, at its own timestamp or `now` (unix seconds) when
+/// it has none, with its value multiplied by `scale` (see `scaled`). Samples
+/// with the same name and labels share a series; non-finite ones are left out.
+pub fn exposition_series(
+    families: &[MetricFamily],
+    template: &NameTemplate,
+    scale: f64,
+    now: f64,
+) -> Vec<Series> {
+    let mut series: Vec<Series> = Vec::new();
+    for sample in families.iter().flat_map(|f| f.samples.iter()) {
+        let value = match scaled(sample.value, scale) {
+            Some(value) => value,
+            None => continue,
+        };
+        let name = template.render(&sample.name, &sample.labels);
+        let point = (sample.timestamp.unwrap_or(now).round() as isize, value);
+        match series.iter_mut().find(|s| s.name == name) {
+            Some(s) => s.data.push(point),
+            None => series.push(Series::new(name, vec![point])),
+        }
+    }
+    series
+}
+
+/// Appends a scrape to a stream, so polling an exporter builds up history.
+pub fn push_exposition(
+    stream: &StreamHandle,
+    text: &str,
+    template: &NameTemplate,
+    scale: f64,
+    now: f64,
+) -> Result<(), PromError> {
+    for series in exposition_series(&parse_exposition(text)?, template, scale, now) {
+        stream.extend(&series.name, series.data);
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    status: String,
+    #[serde(default)]
+    data: Option<QueryData>,
+    #[serde(default, rename = "errorType")]
+    error_type: String,
+    #[serde(default)]
+    error: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryData {
+    result_type: String,
+    result: Vec<QueryResult>,
+}
+
+#[derive(Deserialize)]
+struct QueryResult {
+    #[serde(default)]
+    metric: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    values: Vec<(f64, String)>,
+    #[serde(default)]
+    value: Option<(f64, String)>,
+}
+
+/// Parses a `/api/v1/query_range` (matrix) or `/api/v1/query` (vector)
+/// response into series, with values multiplied by `scale` (see `scaled`).
+/// `NaN` and infinite values are left out.
+pub fn parse_query_range(text: &str, template: &NameTemplate, scale: f64) -> Result<Vec<Series>, PromError> {
+    let response: QueryResponse = serde_json::from_str(text)?;
+    if response.status != "success" {
+        return Err(PromError::Query {
+            kind: response.error_type,
+            message: response.error,
+        });
+    }
+    let data = response.data.ok_or_else(|| PromError::ResultType("none".to_string()))?;
+    if data.result_type != "matrix" && data.result_type != "vector" {
+        return Err(PromError::ResultType(data.result_type));
+    }
+    Ok(data
+        .result
+        .into_iter()
+        .map(|result| {
+            let mut name = String::new();
+            let mut labels: Vec<(String, String)> = Vec::new();
+            for (k, v) in result.metric {
+                let v = v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+                match k.as_str() {
+                    "__name__" => name = v,
+                    _ => labels.push((k, v)),
+                }
+            }
+            let data = result
+                .values
+                .into_iter()
+                .chain(result.value)
+                .filter_map(|(t, v)| {
+                    let v = scaled(parse_value(&v)?, scale)?;
+                    Some((t.round() as isize, v))
+                })
+                .collect();
+            Series::new(template.render(&name, &labels), data)
+        })
+        .collect())
+}
+
+/// A `FetchChart` mapper for a Prometheus-compatible query API.
+pub fn query_range_map(template: NameTemplate, scale: f64) -> MapFn {
+    Rc::new(move |body: &str| parse_query_range(body, &template, scale).map_err(|e| e.to_string()))
+}
+
+/// A `FetchChart` mapper for an exporter's `/metrics` page or a saved
+/// scrape. Samples without a timestamp are placed at `now()`.
+pub fn exposition_map(template: NameTemplate, scale: f64, now: Rc<dyn Fn() -> f64>) -> MapFn {
+    Rc::new(move |body: &str| {
+        parse_exposition(body)
+            .map(|families| exposition_series(&families, &template, scale, now()))
+            .map_err(|e| e.to_string())
+    })
+}
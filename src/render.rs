@@ -0,0 +1,193 @@
+use svg::node::element::tag::Type;
+use svg::node::element::Group;
+use svg::node::Attributes;
+use svg::parser::Event;
+use yew::prelude::*;
+use yew::services::ConsoleService;
+use yew::virtual_dom::{VTag, VText};
+
+use crate::chart::MARGINS;
+
+/// The root `<svg>` of a chart laid out in `width` by `height` units,
+/// stretched to fill its container.
+pub fn svg_root(width: isize, height: isize) -> VTag {
+    let mut root = VTag::new("svg");
+    root.add_attribute("width", &"100%");
+    root.add_attribute("height", &"100%");
+    root.add_attribute("viewBox", &format!("0 0 {} {}", width, height));
+    root.add_attribute("preserveAspectRatio", &"none");
+    root
+}
+
+/// A one line tooltip above the top right corner of the plot of a chart
+/// `width` units wide.
+pub fn tooltip_text(width: isize, text: String) -> Html {
+    let (top, right, _, _) = MARGINS;
+    html! {
+        <text
+            class="tooltip"
+            x=width - right
+            y=top - 40
+            text-anchor="end"
+            font-family="sans-serif"
+            font-size=12
+            pointer-events="none"
+        >
+            {text}
+        </text>
+    }
+}
+
+/// A color key above the top right corner of the plot of a chart `width`
+/// units wide: `colors` from low to high, then the `lo`, optional middle and
+/// `hi` labels under them.
+pub fn color_bar(
+    width: isize,
+    label: &str,
+    colors: Vec<String>,
+    (lo, middle, hi): (String, Option<String>, String),
+) -> Html {
+    let (top, right, _, _) = MARGINS;
+    let (bar_width, height) = (120.0, 10.0);
+    let x0 = (width - right) as f32 - bar_width;
+    let y = top as f32 - 40.0;
+    let step = bar_width / colors.len().max(1) as f32;
+    // a little overlap hides seams between the swatches
+    let swatches = colors.into_iter().enumerate().map(|(i, fill)| {
+        html! {
+            <rect x=x0 + step * i as f32 y=y width=step + 0.5 height=height fill=fill />
+        }
+    });
+    let middle = match middle {
+        Some(text) => html! {
+            <text x=x0 + bar_width / 2.0 y=y + height + 12.0 text-anchor="middle">{text}</text>
+        },
+        None => html! {},
+    };
+    html! {
+        <g class="color-bar" font-family="sans-serif" font-size=10>
+            <text x=x0 y=y - 4.0 font-size=12>{label}</text>
+            { for swatches }
+            <text x=x0 y=y + height + 12.0>{lo}</text>
+            {middle}
+            <text x=x0 + bar_width y=y + height + 12.0 text-anchor="end">{hi}</text>
+        </g>
+    }
+}
+
+/// One swatch and label of a legend.
+pub struct LegendEntry {
+    pub name: String,
+    pub color: String,
+    /// Drawn faded, e.g. while its series is hidden
+    pub faded: bool,
+}
+
+/// Rough width of a legend entry, its swatch, label and the gap after it.
+/// There is no text measurement outside the browser, so labels are assumed
+/// to be 7 units per character at the 12px legend font.
+pub fn legend_entry_width(name: &str) -> f32 {
+    32.0 + 7.0 * name.chars().count() as f32
+}
+
+/// A row of legend entries starting at `(x, y)`, the baseline of the labels.
+/// Clicking an entry sends its name to `on_toggle`; hovering one sends it to
+/// `on_highlight`, and `None` once the pointer leaves.
+pub fn legend(
+    entries: Vec<LegendEntry>,
+    (mut x, y): (f32, f32),
+    on_toggle: &Callback<String>,
+    on_highlight: Option<&Callback<Option<String>>>,
+) -> Html {
+    let entries = entries.into_iter().map(|entry| {
+        let (toggle, enter) = (entry.name.clone(), entry.name.clone());
+        let (enter, leave) = match on_highlight {
+            Some(cb) => (cb.reform(move |_| Some(enter.clone())), cb.reform(|_| None)),
+            None => (Callback::noop(), Callback::noop()),
+        };
+        let view = html! {
+            <g
+                class="legend-entry"
+                style="cursor: pointer"
+                opacity=if entry.faded { 0.4 } else { 1.0 }
+                onclick=on_toggle.reform(move |_| toggle.clone())
+                onmouseenter=enter
+                onmouseleave=leave
+            >
+                <rect x=x y=y - 10.0 width=12 height=12 fill=&entry.color />
+                <text x=x + 16.0 y=y font-family="sans-serif" font-size=12>{&entry.name}</text>
+            </g>
+        };
+        x += legend_entry_width(&entry.name);
+        view
+    });
+    html! {
+        <g class="legend">{ for entries }</g>
+    }
+}
+
+/// Converts a rendered chart into yew nodes appended to `root`.
+///
+/// `visit` sees the attributes of every tag that can have children right
+/// after its node is built, which is where listeners get attached.
+pub fn svg_to_vtag<F>(group: &Group, root: VTag, mut visit: F) -> VTag
+where
+    F: FnMut(&Attributes, &mut VTag),
+{
+    let s = group.to_string();
+    let buf = std::io::BufReader::new(s.as_bytes());
+    let svg = svg::read(buf).unwrap();
+    let mut stack: Vec<VTag> = vec![root];
+    for event in svg {
+        match event {
+            Event::Tag(tag, kind, attrs) => {
+                match kind {
+                    Type::End => {
+                        // done adding children to this tag
+                        let completed = stack.pop().unwrap();
+                        let parent = stack.last_mut().unwrap();
+                        parent.add_child(completed.into());
+                        continue;
+                    }
+                    Type::Start => {
+                        // have start, build tag and attrs
+                        let mut vnode = VTag::new(tag.to_owned());
+                        let mut attributes: Vec<(String, String)> = attrs
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect();
+                        attributes.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+                        vnode.add_attributes(attributes);
+                        visit(&attrs, &mut vnode);
+                        stack.push(vnode);
+                    }
+                    Type::Empty => {
+                        // "empty" tags have no children
+                        let mut vnode = VTag::new(tag.to_owned());
+                        let attributes = attrs
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect();
+                        vnode.add_attributes(attributes);
+                        stack
+                            .last_mut()
+                            .expect("no parent for empty tag")
+                            .add_child(vnode.into());
+                    }
+                }
+            }
+            Event::Text(s) => {
+                let node = VText::new(s.to_string());
+                stack
+                    .last_mut()
+                    .expect("no parent for text item")
+                    .add_child(node.into());
+            }
+            Event::Error(e) => ConsoleService::error(&format!("error parsing svg: {}", e)),
+            Event::Comment => ConsoleService::debug("got comment"),
+            Event::Declaration => ConsoleService::debug("got decl"),
+            Event::Instruction => ConsoleService::debug("go instruction"),
+        }
+    }
+    stack.pop().unwrap()
+}
@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use yew::prelude::*;
+
+use crate::chart::MARGINS;
+use crate::render;
+use crate::series::{self, VIRIDIS};
+use crate::view::{self, Domain, Projection};
+
+// color values are quantized to this many fills, so every point of one fill
+// goes into a single path instead of an element each
+const COLOR_STEPS: usize = 32;
+// how far outside a circle the pointer may be and still hover it, in svg units
+const HOVER_SLACK: f32 = 4.0;
+// half the width of an error bar's caps, in svg units
+const ERROR_CAP: f32 = 3.0;
+
+/// One point of a `ScatterChart`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScatterPoint {
+    pub x: f32,
+    pub y: f32,
+    /// Mapped onto the radius; points without one use the chart's `radius`
+    pub size: Option<f32>,
+    /// Mapped onto `color_ramp`; points without one use the chart's `color`
+    pub color: Option<f32>,
+    /// Shown in the tooltip instead of the coordinates
+    pub label: Option<String>,
+    /// Lower and upper bound of `y`, drawn as an error bar
+    pub bounds: Option<(f32, f32)>,
+}
+
+impl ScatterPoint {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_color(mut self, color: f32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_bounds(mut self, lower: f32, upper: f32) -> Self {
+        self.bounds = Some((lower, upper));
+        self
+    }
+}
+
+/// Maps sizes onto radii so a circle's area, not its radius, grows linearly
+/// with the value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeScale {
+    pub domain: (f32, f32),
+    pub range: (f32, f32),
+}
+
+impl SizeScale {
+    pub fn radius(&self, value: f32) -> f32 {
+        let (lo, hi) = self.domain;
+        let t = if hi > lo { ((value - lo) / (hi - lo)).max(0.0).min(1.0) } else { 0.5 };
+        let (r0, r1) = self.range;
+        (r0 * r0 + (r1 * r1 - r0 * r0) * t).sqrt()
+    }
+}
+
+/// Smallest range holding every finite value, widened if they are all equal.
+fn fit<I: IntoIterator<Item = f32>>(values: I) -> Option<(f32, f32)> {
+    let (lo, hi) = values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    match (lo > hi, lo == hi) {
+        (true, _) => None,
+        (false, true) => Some((lo - 1.0, hi + 1.0)),
+        (false, false) => Some((lo, hi)),
+    }
+}
+
+/// A point as drawn, in svg units.
+#[derive(Clone, Copy, Debug)]
+struct Placed {
+    idx: usize,
+    at: (f32, f32),
+    r: f32,
+}
+
+pub struct ScatterChart {
+    props: Props,
+    axes: Option<svg::node::element::Group>,
+    /// `(fill, path)` per color step
+    layers: Vec<(String, String)>,
+    /// Every error bar as one path
+    errors: String,
+    placed: Vec<Placed>,
+    /// Indices into `placed` by grid cell, so hovering looks at a few points
+    grid: HashMap<(i32, i32), Vec<usize>>,
+    cell: f32,
+    hovered: Option<usize>,
+    node_ref: NodeRef,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    MouseMove(yew::MouseEvent),
+    MouseLeave,
+    Click,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub width: isize,
+    pub height: isize,
+    /// Shared so re-rendering the parent doesn't copy every point. The other
+    /// charts take their data in an `Rc` for the same reason
+    pub points: Rc<Vec<ScatterPoint>>,
+    /// Fitted to the points when unset
+    #[prop_or_default]
+    pub x_domain: Option<(f32, f32)>,
+    #[prop_or_default]
+    pub y_domain: Option<(f32, f32)>,
+    /// Radius of points without a size
+    #[prop_or(3.0)]
+    pub radius: f32,
+    /// Fitted to the sizes when unset
+    #[prop_or_default]
+    pub size_domain: Option<(f32, f32)>,
+    /// Smallest and largest radius sizes are mapped onto
+    #[prop_or((2.0, 20.0))]
+    pub size_range: (f32, f32),
+    /// Fill of points without a color value; the first `PALETTE` color when unset
+    #[prop_or_default]
+    pub color: Option<String>,
+    /// Fitted to the color values when unset
+    #[prop_or_default]
+    pub color_domain: Option<(f32, f32)>,
+    /// Evenly spaced `#RRGGBB` stops color values are mapped onto
+    #[prop_or_else(default_ramp)]
+    pub color_ramp: Vec<String>,
+    #[prop_or(0.7)]
+    pub opacity: f32,
+    /// Titles of the size legend and color bar
+    #[prop_or("size".to_string())]
+    pub size_label: String,
+    #[prop_or("color".to_string())]
+    pub color_label: String,
+    /// Called with the index of the point under the pointer
+    #[prop_or_default]
+    pub on_hover: Option<Callback<Option<usize>>>,
+    #[prop_or_default]
+    pub on_click: Option<Callback<usize>>,
+}
+
+fn default_ramp() -> Vec<String> {
+    VIRIDIS.iter().map(|c| c.to_string()).collect()
+}
+
+impl ScatterChart {
+    fn projection(&self) -> Projection {
+        let (top, right, bottom, left) = MARGINS;
+        let points = self.props.points.iter();
+        let fitted = |d: Option<(f32, f32)>, values: Vec<f32>| d.or_else(|| fit(values)).unwrap_or((0.0, 1.0));
+        let x = fitted(self.props.x_domain, points.clone().map(|p| p.x).collect());
+        let ys = points.flat_map(|p| {
+            let bounds = p.bounds.map(|(lo, hi)| vec![lo, hi]).unwrap_or_default();
+            std::iter::once(p.y).chain(bounds)
+        });
+        let y = fitted(self.props.y_domain, ys.collect());
+        Projection {
+            left: left as f32,
+            top: top as f32,
+            width: (self.props.width - left - right) as f32,
+            height: (self.props.height - top - bottom) as f32,
+            domain: Domain::new(x, y),
+        }
+    }
+
+    fn size_scale(&self) -> Option<SizeScale> {
+        let domain = self
+            .props
+            .size_domain
+            .or_else(|| fit(self.props.points.iter().filter_map(|p| p.size)))?;
+        Some(SizeScale {
+            domain,
+            range: self.props.size_range,
+        })
+    }
+
+    fn color_domain(&self) -> Option<(f32, f32)> {
+        self.props
+            .color_domain
+            .or_else(|| fit(self.props.points.iter().filter_map(|p| p.color)))
+    }
+
+    fn step_color(&self, step: usize) -> String {
+        series::ramp(&self.props.color_ramp, step as f32 / (COLOR_STEPS - 1) as f32)
+    }
+
+    fn rebuild(&mut self) {
+        let proj = self.projection();
+        let (sizes, colors) = (self.size_scale(), self.color_domain());
+        let mut paths = vec![String::new(); COLOR_STEPS + 1];
+        self.errors.clear();
+        self.placed.clear();
+        for (idx, p) in self.props.points.iter().enumerate() {
+            if !proj.domain.contains((p.x, p.y)) {
+                continue;
+            }
+            let r = match (p.size, sizes) {
+                (Some(size), Some(scale)) => scale.radius(size),
+                _ => self.props.radius,
+            };
+            // the last path holds the points without a color value
+            let step = match (p.color, colors) {
+                (Some(c), Some((lo, hi))) if c.is_finite() => {
+                    let t = ((c - lo) / (hi - lo)).max(0.0).min(1.0);
+                    (t * (COLOR_STEPS - 1) as f32).round() as usize
+                }
+                _ => COLOR_STEPS,
+            };
+            let (x, y) = proj.to_pixel((p.x, p.y));
+            if let Some((lo, hi)) = p.bounds.filter(|(lo, hi)| lo.is_finite() && hi.is_finite()) {
+                let clamp = |v: f32| proj.to_pixel((p.x, v.max(proj.domain.y.0).min(proj.domain.y.1))).1;
+                let (top, bottom) = (clamp(hi), clamp(lo));
+                let _ = write!(
+                    self.errors,
+                    "M{x:.1} {t:.1}V{b:.1}M{l:.1} {t:.1}H{r:.1}M{l:.1} {b:.1}H{r:.1}",
+                    x = x,
+                    t = top,
+                    b = bottom,
+                    l = x - ERROR_CAP,
+                    r = x + ERROR_CAP
+                );
+            }
+            // a circle as two arcs, so thousands of them fit in one path
+            let _ = write!(
+                paths[step],
+                "M{:.1} {:.1}m{:.1} 0a{r:.1} {r:.1} 0 1 0 {d:.1} 0a{r:.1} {r:.1} 0 1 0 {n:.1} 0",
+                x,
+                y,
+                -r,
+                r = r,
+                d = 2.0 * r,
+                n = -2.0 * r
+            );
+            self.placed.push(Placed { idx, at: (x, y), r });
+        }
+        let fallback = series::palette_color(self.props.color.as_deref(), 0);
+        self.layers = paths
+            .into_iter()
+            .enumerate()
+            .filter(|(_, d)| !d.is_empty())
+            .map(|(step, d)| match step == COLOR_STEPS {
+                true => (fallback.clone(), d),
+                false => (self.step_color(step), d),
+            })
+            .collect();
+        let reach = self.placed.iter().map(|p| p.r).fold(0.0, f32::max) + HOVER_SLACK;
+        self.cell = reach.max(1.0);
+        self.grid.clear();
+        for (i, p) in self.placed.iter().enumerate() {
+            self.grid.entry(cell_of(p.at, self.cell)).or_default().push(i);
+        }
+        self.hovered = None;
+        self.axes = Some(axes(&proj, self.props.width, self.props.height));
+    }
+
+    /// The drawn point under `p`, preferring the one whose edge is closest.
+    fn point_at(&self, p: (f32, f32)) -> Option<usize> {
+        let (cx, cy) = cell_of(p, self.cell);
+        // cells are as wide as the largest reach, so the neighbours cover every candidate
+        (cx - 1..=cx + 1)
+            .flat_map(|x| (cy - 1..=cy + 1).map(move |y| (x, y)))
+            .filter_map(|key| self.grid.get(&key))
+            .flatten()
+            .map(|i| {
+                let placed = &self.placed[*i];
+                let dist = ((placed.at.0 - p.0).powi(2) + (placed.at.1 - p.1).powi(2)).sqrt();
+                (dist - placed.r, *i)
+            })
+            .filter(|(gap, _)| *gap <= HOVER_SLACK)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map(|(_, i)| i)
+    }
+
+    fn svg_point(&self, e: &yew::MouseEvent) -> Option<(f32, f32)> {
+        let view_box = (0.0, 0.0, self.props.width as f32, self.props.height as f32);
+        view::client_to_svg(&self.node_ref, view_box, (e.client_x(), e.client_y()))
+    }
+
+    fn set_hovered(&mut self, hovered: Option<usize>) -> ShouldRender {
+        if hovered == self.hovered {
+            return false;
+        }
+        self.hovered = hovered;
+        if let Some(cb) = &self.props.on_hover {
+            cb.emit(hovered.map(|i| self.placed[i].idx));
+        }
+        true
+    }
+
+    fn errors_view(&self) -> Html {
+        if self.errors.is_empty() {
+            return html! {};
+        }
+        html! {
+            <path class="error-bars" d=&self.errors fill="none" stroke="#333333" stroke-width=1 pointer-events="none" />
+        }
+    }
+
+    fn points_view(&self) -> Html {
+        let paths = self.layers.iter().map(|(fill, d)| {
+            html! {
+                <path d=d fill=fill fill-opacity=self.props.opacity stroke="none" />
+            }
+        });
+        html! {
+            <g class="points" pointer-events="none">{ for paths }</g>
+        }
+    }
+
+    fn tooltip_view(&self) -> Html {
+        let placed = match self.hovered.map(|i| self.placed[i]) {
+            Some(placed) => placed,
+            None => return html! {},
+        };
+        let p = &self.props.points[placed.idx];
+        let mut text = match &p.label {
+            Some(label) => label.clone(),
+            None => format!("({}, {})", p.x, p.y),
+        };
+        if let Some((lo, hi)) = p.bounds {
+            let _ = write!(text, " [{}, {}]", lo, hi);
+        }
+        if let Some(size) = p.size {
+            let _ = write!(text, " {}: {}", self.props.size_label, size);
+        }
+        if let Some(color) = p.color {
+            let _ = write!(text, " {}: {}", self.props.color_label, color);
+        }
+        let (x, y) = placed.at;
+        html! {
+            <g class="tooltip" pointer-events="none">
+                <circle cx=x cy=y r=placed.r + 2.0 fill="none" stroke="#333333" />
+                <text x=x y=y - placed.r - 8.0 text-anchor="middle" font-family="sans-serif" font-size=12>
+                    {text}
+                </text>
+            </g>
+        }
+    }
+
+    /// Circles for the smallest, middle and largest size.
+    fn size_legend_view(&self) -> Html {
+        let scale = match self.size_scale() {
+            Some(scale) if self.props.points.iter().any(|p| p.size.is_some()) => scale,
+            _ => return html! {},
+        };
+        let (top, _, _, left) = MARGINS;
+        let (lo, hi) = scale.domain;
+        // circles share a baseline so their sizes are easy to compare
+        let baseline = top as f32 - 12.0;
+        let mut x = left as f32 + 40.0;
+        let entries = [lo, (lo + hi) / 2.0, hi]
+            .iter()
+            .map(|v| {
+                let r = scale.radius(*v);
+                let entry = html! {
+                    <g>
+                        <circle cx=x + r cy=baseline - r r=r fill="none" stroke="#333333" />
+                        <text x=x + r y=baseline + 10.0 text-anchor="middle" font-family="sans-serif" font-size=10>
+                            {format!("{}", v)}
+                        </text>
+                    </g>
+                };
+                x += 2.0 * r + 16.0;
+                entry
+            })
+            .collect::<Vec<Html>>();
+        html! {
+            <g class="size-legend">
+                <text x=left y=baseline - 4.0 font-family="sans-serif" font-size=12>{&self.props.size_label}</text>
+                { for entries.into_iter() }
+            </g>
+        }
+    }
+
+    /// A ramp of the color steps with the ends of the color domain.
+    fn color_bar_view(&self) -> Html {
+        let (lo, hi) = match self.color_domain() {
+            Some(domain) if self.props.points.iter().any(|p| p.color.is_some()) => domain,
+            _ => return html! {},
+        };
+        let colors = (0..COLOR_STEPS).map(|i| self.step_color(i)).collect();
+        let labels = (format!("{}", lo), None, format!("{}", hi));
+        render::color_bar(self.props.width, &self.props.color_label, colors, labels)
+    }
+}
+
+fn cell_of((x, y): (f32, f32), cell: f32) -> (i32, i32) {
+    ((x / cell).floor() as i32, (y / cell).floor() as i32)
+}
+
+/// Axes for the plot area, drawn by the charts crate so they match `ChartComponent`.
+fn axes(proj: &Projection, width: isize, height: isize) -> svg::node::element::Group {
+    let (top, right, bottom, left) = MARGINS;
+    let d = proj.domain;
+    let x = charts::ScaleLinear::new()
+        .set_domain(vec![d.x.0, d.x.1])
+        .set_range(vec![0, width - left - right]);
+    let y = charts::ScaleLinear::new()
+        .set_domain(vec![d.y.0, d.y.1])
+        .set_range(vec![height - top - bottom, 0]);
+    charts::Chart::new()
+        .set_width(width)
+        .set_height(height)
+        .set_margins(top, right, bottom, left)
+        .add_axis_bottom(&x)
+        .add_axis_left(&y)
+        .to_svg()
+        .unwrap()
+}
+
+impl Component for ScatterChart {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut chart = Self {
+            props,
+            axes: None,
+            layers: Vec::new(),
+            errors: String::new(),
+            placed: Vec::new(),
+            grid: HashMap::new(),
+            cell: 1.0,
+            hovered: None,
+            node_ref: NodeRef::default(),
+            link,
+        };
+        chart.rebuild();
+        chart
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseMove(e) => {
+                let hovered = self.svg_point(&e).and_then(|p| self.point_at(p));
+                self.set_hovered(hovered)
+            }
+            Msg::MouseLeave => self.set_hovered(None),
+            Msg::Click => {
+                if let (Some(cb), Some(i)) = (&self.props.on_click, self.hovered) {
+                    cb.emit(self.placed[i].idx);
+                }
+                false
+            }
+        }
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        self.rebuild();
+        true
+    }
+    fn view(&self) -> Html {
+        let mut root = render::svg_root(self.props.width, self.props.height);
+        root.node_ref = self.node_ref.clone();
+        // one listener for every point; `point_at` finds which one is under the pointer
+        root.add_listener(Rc::new(yew::html::onmousemove::Wrapper::new(self.link.callback(Msg::MouseMove))));
+        root.add_listener(Rc::new(yew::html::onmouseleave::Wrapper::new(self.link.callback(|_| Msg::MouseLeave))));
+        root.add_listener(Rc::new(yew::html::onclick::Wrapper::new(self.link.callback(|_| Msg::Click))));
+        if self.hovered.is_some() && self.props.on_click.is_some() {
+            root.add_attribute("style", &"cursor: pointer");
+        }
+        let mut node = render::svg_to_vtag(self.axes.as_ref().unwrap(), root, |_, _| {});
+        node.add_child(self.errors_view());
+        node.add_child(self.points_view());
+        node.add_child(self.size_legend_view());
+        node.add_child(self.color_bar_view());
+        node.add_child(self.tooltip_view());
+        node.into()
+    }
+}
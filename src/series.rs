@@ -0,0 +1,134 @@
+use crate::curve::{Area, Curve};
+use crate::view::Domain;
+
+/// d3's category10, used for series without an explicit color.
+pub const PALETTE: [&str; 10] = [
+    "#1F77B4", "#FF7F0E", "#2CA02C", "#D62728", "#9467BD", "#8C564B", "#E377C2", "#7F7F7F",
+    "#BCBD22", "#17BECF",
+];
+
+/// viridis, for values mapped onto color.
+pub const VIRIDIS: [&str; 5] = ["#440154", "#3B528B", "#21918C", "#5EC962", "#FDE725"];
+
+/// ColorBrewer's RdBu reversed, for values diverging from a midpoint.
+pub const BLUE_RED: [&str; 5] = ["#2166AC", "#67A9CF", "#F7F7F7", "#EF8A62", "#B2182B"];
+
+/// One named line of data in a `ChartComponent`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub data: Vec<(isize, isize)>,
+    /// Hex color such as `#FF0000`; picked from `PALETTE` when unset
+    pub color: Option<String>,
+    /// Lower and upper bound of each point in `data`, shaded as a band
+    /// around the line
+    pub bounds: Vec<(isize, isize)>,
+    /// How the line joins the points; ignored when the chart is stacked
+    pub curve: Curve,
+    /// Fill under the line; ignored when the chart is stacked
+    pub area: Option<Area>,
+}
+
+impl Series {
+    pub fn new<S: Into<String>>(name: S, data: Vec<(isize, isize)>) -> Self {
+        Self {
+            name: name.into(),
+            data,
+            color: None,
+            bounds: Vec::new(),
+            curve: Curve::Linear,
+            area: None,
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: Vec<(isize, isize)>) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn with_curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_area(mut self, area: Area) -> Self {
+        self.area = Some(area);
+        self
+    }
+
+    pub fn with_color<S: Into<String>>(mut self, color: S) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// The color to draw the `idx`th series of a chart with.
+    pub fn color_or_default(&self, idx: usize) -> String {
+        palette_color(self.color.as_deref(), idx)
+    }
+
+    /// Smallest domain containing every point of `series` and its bounds,
+    /// or `None` if they are all empty.
+    pub fn extent<'a, I: IntoIterator<Item = &'a Series>>(series: I) -> Option<Domain> {
+        let mut points = series.into_iter().flat_map(|s| {
+            let bounds = s.data.iter().zip(&s.bounds).flat_map(|((x, _), (lo, hi))| vec![(*x, *lo), (*x, *hi)]);
+            s.data.iter().cloned().chain(bounds).map(|(x, y)| (x as f32, y as f32))
+        });
+        let (x0, y0) = points.next()?;
+        let d = points.fold(Domain::new((x0, x0), (y0, y0)), |d, (x, y)| {
+            Domain::new((d.x.0.min(x), d.x.1.max(x)), (d.y.0.min(y), d.y.1.max(y)))
+        });
+        // a single value still needs some room to be drawn in
+        let widen = |(lo, hi): (f32, f32)| if lo == hi { (lo - 1.0, hi + 1.0) } else { (lo, hi) };
+        Some(Domain::new(widen(d.x), widen(d.y)))
+    }
+}
+
+/// `color` if set, else the palette color for the `idx`th item of a chart.
+pub fn palette_color(color: Option<&str>, idx: usize) -> String {
+    color.unwrap_or(PALETTE[idx % PALETTE.len()]).to_string()
+}
+
+/// Blends a `#RRGGBB` color towards white, for de-emphasised series.
+/// Anything else is returned as is.
+pub fn dim(color: &str) -> String {
+    let [r, g, b] = match rgb(color) {
+        Some(rgb) => rgb,
+        None => return color.to_string(),
+    };
+    let channel = |c: f32| (c + (255.0 - c) * 0.75) as u8;
+    format!("#{:02X}{:02X}{:02X}", channel(r), channel(g), channel(b))
+}
+
+fn rgb(color: &str) -> Option<[f32; 3]> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    // `get` rather than indexing, a multi-byte character can be six bytes long
+    let channel = |i: usize| {
+        let pair = hex.get(i..i + 2)?;
+        u8::from_str_radix(pair, 16).ok().map(f32::from)
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// The color `t` (0 to 1) of the way along evenly spaced `#RRGGBB` stops.
+pub fn ramp<S: AsRef<str>>(stops: &[S], t: f32) -> String {
+    let [r, g, b] = ramp_rgb(stops, t);
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// `ramp` as red, green and blue bytes, e.g. for bitmaps.
+pub fn ramp_rgb<S: AsRef<str>>(stops: &[S], t: f32) -> [u8; 3] {
+    let mut colors: Vec<[f32; 3]> = stops.iter().filter_map(|s| rgb(s.as_ref())).collect();
+    if colors.is_empty() {
+        colors.extend(rgb(PALETTE[0]));
+    }
+    let t = if t.is_nan() { 0.0 } else { t.max(0.0).min(1.0) };
+    let pos = t * (colors.len() - 1) as f32;
+    let idx = (pos.floor() as usize).min(colors.len() - 1);
+    let (a, b) = (colors[idx], colors[(idx + 1).min(colors.len() - 1)]);
+    let f = pos - idx as f32;
+    let channel = |i: usize| (a[i] + (b[i] - a[i]) * f).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
@@ -0,0 +1,504 @@
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::time::Duration;
+use thiserror::Error;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::format::Text;
+use yew::prelude::*;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::services::websocket::{WebSocketService, WebSocketStatus};
+use yew::services::ConsoleService;
+
+use crate::stream::StreamHandle;
+
+#[derive(Error, Debug)]
+pub enum SourceError {
+    #[error("invalid json message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("line {line}: {reason}")]
+    Line { line: usize, reason: String },
+    #[error("sample {sample}: x {x} is not a finite number within range")]
+    X { sample: usize, x: f64 },
+    #[error("sample {sample}: y {y} is not a finite number within range")]
+    Y { sample: usize, y: f64 },
+}
+
+/// A point and the name of the series it belongs to.
+pub type Sample = (String, (isize, isize));
+
+/// How incoming messages are turned into samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// `{"series": "cpu", "x": 1600000000, "y": 42}` or an array of those
+    Json,
+    /// Influx line protocol, e.g. `cpu,host=a usage=42 1600000000000000000`.
+    /// Each numeric field becomes the series `measurement.field`, or just
+    /// `measurement` for a field named `value`; string and boolean fields
+    /// are skipped. Timestamps are in nanoseconds and become x in the given
+    /// precision.
+    LineProtocol(Precision),
+}
+
+/// The unit x is given in for line protocol timestamps. Only seconds keep
+/// current epoch times within `isize` on 32 bit targets such as wasm32.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+}
+
+impl Precision {
+    fn nanos(self) -> i64 {
+        match self {
+            Precision::Nanoseconds => 1,
+            Precision::Microseconds => 1_000,
+            Precision::Milliseconds => 1_000_000,
+            Precision::Seconds => 1_000_000_000,
+        }
+    }
+}
+
+/// `v` as an isize if it is finite and in range, dropping any fraction.
+fn whole(v: f64) -> Option<isize> {
+    // isize::MAX rounds up as an f64, so the upper bound is exclusive
+    match v.is_finite() && v >= isize::MIN as f64 && v < isize::MAX as f64 {
+        true => Some(v as isize),
+        false => None,
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonSample {
+    #[serde(default)]
+    series: String,
+    x: f64,
+    y: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonMessage {
+    One(JsonSample),
+    Many(Vec<JsonSample>),
+}
+
+impl Format {
+    /// Parses one message into `(series, point)` samples.
+    pub fn parse(&self, text: &str) -> Result<Vec<Sample>, SourceError> {
+        match self {
+            Format::Json => {
+                let samples = match serde_json::from_str(text)? {
+                    JsonMessage::One(s) => vec![s],
+                    JsonMessage::Many(s) => s,
+                };
+                samples
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, s)| {
+                        let x = whole(s.x).ok_or(SourceError::X { sample: idx, x: s.x })?;
+                        let y = whole(s.y.round()).ok_or(SourceError::Y { sample: idx, y: s.y })?;
+                        Ok((s.series, (x, y)))
+                    })
+                    .collect()
+            }
+            Format::LineProtocol(precision) => {
+                let mut samples = Vec::new();
+                for (idx, line) in text.lines().enumerate() {
+                    samples.extend(parse_line(idx + 1, line, *precision)?);
+                }
+                Ok(samples)
+            }
+        }
+    }
+}
+
+/// Splits line protocol `text` at each `sep` that isn't escaped with a
+/// backslash or, when `quoted`, inside a double quoted string value.
+fn split_unescaped(text: &str, sep: char, quoted: bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut escaped, mut in_quotes) = (0, false, false);
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' if quoted => in_quotes = !in_quotes,
+            _ if c == sep && !in_quotes => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn unescape(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next)) if matches!(next, ' ' | ',' | '=') => {
+                out.push(next);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A field value as a number, or `None` for strings, booleans and numbers
+/// too large to chart.
+fn numeric(value: &str) -> Option<isize> {
+    // integers carry an `i` suffix in line protocol, unsigned ones a `u`
+    let number = value.strip_suffix('i').or_else(|| value.strip_suffix('u')).unwrap_or(value);
+    // `parse` would take `inf` and `nan`, which line protocol doesn't have
+    if !number.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.') {
+        return None;
+    }
+    whole(number.parse::<f64>().ok()?.round())
+}
+
+fn parse_line(line: usize, text: &str, precision: Precision) -> Result<Vec<Sample>, SourceError> {
+    let text = text.trim();
+    if text.is_empty() || text.starts_with('#') {
+        return Ok(Vec::new());
+    }
+    let err = |reason: &str| SourceError::Line {
+        line,
+        reason: reason.to_string(),
+    };
+    // quotes only delimit string field values, in the key they are literal
+    let key = split_unescaped(text, ' ', false)[0];
+    let mut parts = split_unescaped(&text[key.len()..], ' ', true).into_iter().filter(|p| !p.is_empty());
+    let fields = parts.next().ok_or_else(|| err("missing fields"))?;
+    let timestamp = parts.next().ok_or_else(|| err("missing timestamp"))?;
+    let nanos: i64 = match timestamp.parse() {
+        Ok(nanos) => nanos,
+        // some writers format timestamps as floats, e.g. `1.6e18`
+        Err(_) => timestamp
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite() && t.abs() < i64::MAX as f64)
+            .map(|t| t as i64)
+            .ok_or_else(|| err("timestamp is not a number"))?,
+    };
+    let timestamp = isize::try_from(nanos.div_euclid(precision.nanos()))
+        .map_err(|_| err("timestamp is out of range at this precision"))?;
+    let measurement = unescape(split_unescaped(key, ',', false)[0]);
+    let mut samples = Vec::new();
+    for field in split_unescaped(fields, ',', true) {
+        let kv = split_unescaped(field, '=', true);
+        let name = unescape(kv[0]);
+        let value = match kv.get(1) {
+            Some(value) => value,
+            None => return Err(err(&format!("field `{}` has no value", name))),
+        };
+        if let Some(value) = numeric(value) {
+            let series = match name.as_str() {
+                "value" => measurement.clone(),
+                _ => format!("{}.{}", measurement, name),
+            };
+            samples.push((series, (timestamp, value)));
+        }
+    }
+    match samples.is_empty() {
+        true => Err(err("no numeric fields")),
+        false => Ok(samples),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransportEvent {
+    Opened,
+    Message(String),
+    Closed,
+    Error(String),
+}
+
+/// Keeps a connection open until dropped.
+pub trait Connection {}
+
+/// Something that can deliver text messages from a url. Swap in your own
+/// implementation to drive a `SourceComponent` from tests or other channels.
+pub trait Transport {
+    fn open(&self, url: &str, events: Callback<TransportEvent>) -> Result<Box<dyn Connection>, String>;
+}
+
+impl Connection for yew::services::websocket::WebSocketTask {}
+
+pub struct WebSocketTransport;
+
+impl Transport for WebSocketTransport {
+    fn open(&self, url: &str, events: Callback<TransportEvent>) -> Result<Box<dyn Connection>, String> {
+        let messages = events.reform(|text: Text| match text {
+            Ok(text) => TransportEvent::Message(text),
+            Err(e) => TransportEvent::Error(e.to_string()),
+        });
+        let status = events.reform(|status| match status {
+            WebSocketStatus::Opened => TransportEvent::Opened,
+            WebSocketStatus::Closed => TransportEvent::Closed,
+            WebSocketStatus::Error => TransportEvent::Error("websocket error".to_string()),
+        });
+        let task = WebSocketService::connect_text(url, messages, status).map_err(str::to_string)?;
+        Ok(Box::new(task))
+    }
+}
+
+struct SseConnection {
+    source: web_sys::EventSource,
+    _listeners: Vec<Closure<dyn FnMut(web_sys::Event)>>,
+}
+
+impl Connection for SseConnection {}
+
+impl Drop for SseConnection {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+pub struct SseTransport;
+
+impl Transport for SseTransport {
+    fn open(&self, url: &str, events: Callback<TransportEvent>) -> Result<Box<dyn Connection>, String> {
+        let source = web_sys::EventSource::new(url).map_err(|e| format!("{:?}", e))?;
+        let on_open = {
+            let events = events.clone();
+            Closure::wrap(Box::new(move |_: web_sys::Event| events.emit(TransportEvent::Opened))
+                as Box<dyn FnMut(web_sys::Event)>)
+        };
+        let on_message = {
+            let events = events.clone();
+            Closure::wrap(Box::new(move |e: web_sys::Event| {
+                let data = e.dyn_into::<web_sys::MessageEvent>().ok().and_then(|e| e.data().as_string());
+                if let Some(data) = data {
+                    events.emit(TransportEvent::Message(data));
+                }
+            }) as Box<dyn FnMut(web_sys::Event)>)
+        };
+        // the browser retries on its own, but we close and back off like the websocket path
+        let on_error = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            events.emit(TransportEvent::Error("event source error".to_string()))
+        }) as Box<dyn FnMut(web_sys::Event)>);
+        source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        Ok(Box::new(SseConnection {
+            source,
+            _listeners: vec![on_open, on_message, on_error],
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SourceKind {
+    WebSocket,
+    ServerSentEvents,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected,
+    /// Waiting to retry after the connection dropped `attempt` times in a row
+    Reconnecting { attempt: u32, reason: String },
+}
+
+/// Delays between reconnects: `base` after the first failure, doubling on
+/// each further one up to `max`, and back to `base` once connected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max, attempt: 0 }
+    }
+
+    /// Counts a failure and returns how long to wait before retrying.
+    pub fn fail(&mut self) -> Duration {
+        self.attempt = self.attempt.saturating_add(1);
+        let factor = 2u32.saturating_pow(self.attempt - 1).min(1 << 16);
+        self.base.checked_mul(factor).unwrap_or(self.max).min(self.max)
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Failures since the last reset.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// Feeds samples from a WebSocket or SSE endpoint into a `StreamHandle`,
+/// showing the connection status over its children (usually the chart).
+pub struct SourceComponent {
+    props: Props,
+    status: ConnectionStatus,
+    backoff: Backoff,
+    connection: Option<Box<dyn Connection>>,
+    retry_task: Option<TimeoutTask>,
+    link: ComponentLink<Self>,
+}
+
+pub enum Msg {
+    Transport(TransportEvent),
+    Retry,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub url: String,
+    pub stream: StreamHandle,
+    #[prop_or(SourceKind::WebSocket)]
+    pub kind: SourceKind,
+    #[prop_or(Format::Json)]
+    pub format: Format,
+    /// Overrides the transport picked by `kind`
+    #[prop_or_default]
+    pub transport: Option<Rc<dyn Transport>>,
+    /// Delay before the first reconnect, doubled on each further failure
+    #[prop_or(Duration::from_millis(500))]
+    pub backoff: Duration,
+    #[prop_or(Duration::from_secs(30))]
+    pub max_backoff: Duration,
+    #[prop_or_default]
+    pub on_status: Option<Callback<ConnectionStatus>>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+impl SourceComponent {
+    fn connect(&mut self) {
+        self.connection = None;
+        self.retry_task = None;
+        self.set_status(ConnectionStatus::Connecting);
+        let events = self.link.callback(Msg::Transport);
+        let opened = match &self.props.transport {
+            Some(transport) => transport.open(&self.props.url, events),
+            None => match self.props.kind {
+                SourceKind::WebSocket => WebSocketTransport.open(&self.props.url, events),
+                SourceKind::ServerSentEvents => SseTransport.open(&self.props.url, events),
+            },
+        };
+        match opened {
+            Ok(connection) => self.connection = Some(connection),
+            Err(reason) => self.schedule_retry(reason),
+        }
+    }
+
+    fn schedule_retry(&mut self, reason: String) {
+        self.connection = None;
+        let delay = self.backoff.fail();
+        self.retry_task = Some(TimeoutService::spawn(delay, self.link.callback(|_| Msg::Retry)));
+        self.set_status(ConnectionStatus::Reconnecting {
+            attempt: self.backoff.attempt(),
+            reason,
+        });
+    }
+
+    fn set_status(&mut self, status: ConnectionStatus) {
+        if let Some(cb) = &self.props.on_status {
+            cb.emit(status.clone());
+        }
+        self.status = status;
+    }
+
+    fn status_view(&self) -> Html {
+        let (label, color) = match &self.status {
+            ConnectionStatus::Connecting => ("connecting".to_string(), "#FF7F0E"),
+            ConnectionStatus::Connected => ("live".to_string(), "#2CA02C"),
+            ConnectionStatus::Reconnecting { attempt, reason } => {
+                (format!("reconnecting ({}): {}", attempt, reason), "#D62728")
+            }
+        };
+        html! {
+            <span
+                class="source-status"
+                style=format!("position: absolute; top: 4px; right: 8px; font: 12px sans-serif; color: {}", color)
+            >
+                {label}
+            </span>
+        }
+    }
+}
+
+impl Component for SourceComponent {
+    type Message = Msg;
+    type Properties = Props;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut source = Self {
+            backoff: Backoff::new(props.backoff, props.max_backoff),
+            props,
+            status: ConnectionStatus::Connecting,
+            connection: None,
+            retry_task: None,
+            link,
+        };
+        source.connect();
+        source
+    }
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Transport(TransportEvent::Opened) => {
+                self.backoff.reset();
+                self.set_status(ConnectionStatus::Connected);
+            }
+            Msg::Transport(TransportEvent::Message(text)) => {
+                match self.props.format.parse(&text) {
+                    Ok(samples) => {
+                        for (series, point) in samples {
+                            self.props.stream.push(&series, point);
+                        }
+                    }
+                    Err(e) => ConsoleService::error(&format!("dropping message: {}", e)),
+                }
+                return false;
+            }
+            Msg::Transport(TransportEvent::Closed) => {
+                self.update(Msg::Transport(TransportEvent::Error("connection closed".to_string())));
+            }
+            Msg::Transport(TransportEvent::Error(reason)) => {
+                // errors are usually followed by a close; only count the first
+                if self.retry_task.is_some() {
+                    return false;
+                }
+                self.schedule_retry(reason);
+            }
+            Msg::Retry => self.connect(),
+        }
+        true
+    }
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let reconnect = props.url != self.props.url
+            || props.kind != self.props.kind
+            || props.stream != self.props.stream;
+        self.backoff.base = props.backoff;
+        self.backoff.max = props.max_backoff;
+        self.props = props;
+        if reconnect {
+            self.backoff.reset();
+            self.connect();
+        }
+        true
+    }
+    fn view(&self) -> Html {
+        html! {
+            <div class="source" style="position: relative">
+                { self.props.children.clone() }
+                { self.status_view() }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,635 @@
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use svg::node::element::{Circle, ClipPath, Definitions, Group, Line, Path, Rectangle, Text};
+use thiserror::Error;
+use yew::prelude::*;
+
+use crate::chart::MARGINS;
+use crate::curve::{self, Curve};
+use crate::render;
+use crate::series::PALETTE;
+use crate::transform::{self, Transform, TransformError};
+use crate::view::{Domain, Projection};
+
+// numbers each built chart, so clip paths of charts on one page don't clash
+static SPECS: AtomicUsize = AtomicUsize::new(0);
+
+/// Space around the plot: top, right, bottom, left.
+pub type Margins = (isize, isize, isize, isize);
+
+/// One record of tabular input, keyed by field name.
+pub type Row = serde_json::Map<String, serde_json::Value>;
+
+#[derive(Error, Debug)]
+pub enum SpecError {
+    #[error("invalid chart spec: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("`{field}` must be greater than {min}, got {value}")]
+    TooSmall {
+        field: &'static str,
+        min: isize,
+        value: isize,
+    },
+    #[error("spec has no data: give `series` or `data` with an `encoding`")]
+    NoData,
+    #[error("`data` needs an `encoding` saying which fields are x and y")]
+    MissingEncoding,
+    #[error("row {row} has no field `{field}`")]
+    MissingField { row: usize, field: String },
+    #[error("row {row}: field `{field}` is not a number")]
+    NotNumeric { row: usize, field: String },
+    #[error("series `{0}` has no points")]
+    EmptySeries(String),
+    #[error("`{0}` is not a #RRGGBB color")]
+    BadColor(String),
+    #[error("{axis} domain [{min}, {max}] is empty")]
+    EmptyDomain { axis: &'static str, min: f32, max: f32 },
+    #[error("annotation {0}: a rule needs exactly one of `x` or `y`")]
+    BadRule(usize),
+    #[error("transform failed: {0}")]
+    Transform(#[from] TransformError),
+    #[error("failed to render chart: {0}")]
+    Render(String),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    Line,
+    Scatter,
+    Area,
+}
+
+impl Default for ChartKind {
+    fn default() -> Self {
+        ChartKind::Line
+    }
+}
+
+/// A chart described as data, e.g. loaded from a dashboard definition.
+///
+/// ```json
+/// {
+///   "kind": "line",
+///   "title": "Requests",
+///   "data": {"values": [{"t": 0, "n": 3, "host": "a"}, {"t": 1, "n": 5, "host": "a"}]},
+///   "encoding": {"x": {"field": "t"}, "y": {"field": "n"}, "color": {"field": "host"}},
+///   "y": {"label": "count", "scale": {"zero": true}},
+///   "annotations": [{"type": "rule", "y": 4, "label": "target"}]
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChartSpec {
+    #[serde(default)]
+    pub kind: ChartKind,
+    #[serde(default = "default_width")]
+    pub width: isize,
+    #[serde(default = "default_height")]
+    pub height: isize,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Series given point by point
+    #[serde(default)]
+    pub series: Vec<SeriesSpec>,
+    /// Rows turned into series through `encoding`
+    #[serde(default)]
+    pub data: Option<DataSpec>,
+    /// Applied to `data` rows, in order, before `encoding`
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+    #[serde(default)]
+    pub encoding: Option<Encoding>,
+    #[serde(default)]
+    pub x: AxisSpec,
+    #[serde(default)]
+    pub y: AxisSpec,
+    /// Palette for series without their own color
+    #[serde(default)]
+    pub colors: Vec<String>,
+    #[serde(default)]
+    pub legend: LegendSpec,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+fn default_width() -> isize {
+    800
+}
+
+fn default_height() -> isize {
+    600
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeriesSpec {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    pub points: Vec<(f32, f32)>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataSpec {
+    pub values: Vec<Row>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Encoding {
+    pub x: FieldRef,
+    pub y: FieldRef,
+    /// Splits rows into one series per distinct value of this field
+    #[serde(default)]
+    pub color: Option<FieldRef>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldRef {
+    pub field: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AxisSpec {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub scale: ScaleSpec,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScaleSpec {
+    /// Fixed extent; fitted to the data when absent
+    #[serde(default)]
+    pub domain: Option<(f32, f32)>,
+    /// Stretch a fitted domain to include zero
+    #[serde(default)]
+    pub zero: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LegendPosition {
+    Top,
+    Bottom,
+    Right,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LegendSpec {
+    #[serde(default = "default_show")]
+    pub show: bool,
+    #[serde(default = "default_position")]
+    pub position: LegendPosition,
+}
+
+fn default_show() -> bool {
+    true
+}
+
+fn default_position() -> LegendPosition {
+    LegendPosition::Top
+}
+
+impl Default for LegendSpec {
+    fn default() -> Self {
+        Self {
+            show: default_show(),
+            position: default_position(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Annotation {
+    /// A horizontal (`y`) or vertical (`x`) line across the plot
+    Rule {
+        #[serde(default)]
+        x: Option<f32>,
+        #[serde(default)]
+        y: Option<f32>,
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        color: Option<String>,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        #[serde(default)]
+        color: Option<String>,
+    },
+}
+
+/// A series after `series` and `data` have been resolved against the spec.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedSeries {
+    pub name: String,
+    pub color: String,
+    pub points: Vec<(f32, f32)>,
+}
+
+fn check_color(color: &str) -> Result<(), SpecError> {
+    let hex = color.trim_start_matches('#');
+    if color.starts_with('#') && hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(SpecError::BadColor(color.to_string()))
+    }
+}
+
+fn number(row: &Row, idx: usize, field: &str) -> Result<f32, SpecError> {
+    let value = row.get(field).ok_or_else(|| SpecError::MissingField {
+        row: idx,
+        field: field.to_string(),
+    })?;
+    value.as_f64().map(|v| v as f32).ok_or_else(|| SpecError::NotNumeric {
+        row: idx,
+        field: field.to_string(),
+    })
+}
+
+fn label(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => String::new(),
+        Some(v) => v.to_string(),
+    }
+}
+
+impl ChartSpec {
+    pub fn from_json(text: &str) -> Result<Self, SpecError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Checks everything that doesn't depend on resolving the data.
+    pub fn validate(&self) -> Result<(), SpecError> {
+        let (top, right, bottom, left) = MARGINS;
+        if self.width <= left + right {
+            return Err(SpecError::TooSmall {
+                field: "width",
+                min: left + right,
+                value: self.width,
+            });
+        }
+        if self.height <= top + bottom {
+            return Err(SpecError::TooSmall {
+                field: "height",
+                min: top + bottom,
+                value: self.height,
+            });
+        }
+        if self.series.is_empty() && self.data.is_none() {
+            return Err(SpecError::NoData);
+        }
+        if self.data.is_some() && self.encoding.is_none() {
+            return Err(SpecError::MissingEncoding);
+        }
+        for color in self.colors.iter().chain(self.series.iter().filter_map(|s| s.color.as_ref())) {
+            check_color(color)?;
+        }
+        for (axis, spec) in &[("x", &self.x), ("y", &self.y)] {
+            if let Some((min, max)) = spec.scale.domain {
+                if min.is_nan() || max.is_nan() || min >= max {
+                    return Err(SpecError::EmptyDomain { axis, min, max });
+                }
+            }
+        }
+        for (idx, annotation) in self.annotations.iter().enumerate() {
+            let color = match annotation {
+                Annotation::Rule { x, y, color, .. } => {
+                    if x.is_some() == y.is_some() {
+                        return Err(SpecError::BadRule(idx));
+                    }
+                    color
+                }
+                Annotation::Text { color, .. } => color,
+            };
+            if let Some(color) = color {
+                check_color(color)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn palette(&self, idx: usize) -> String {
+        match self.colors.is_empty() {
+            true => PALETTE[idx % PALETTE.len()].to_string(),
+            false => self.colors[idx % self.colors.len()].clone(),
+        }
+    }
+
+    /// Resolves explicit series and encoded rows into drawable series.
+    pub fn resolve(&self) -> Result<Vec<ResolvedSeries>, SpecError> {
+        let mut named: Vec<(String, Option<String>, Vec<(f32, f32)>)> = self
+            .series
+            .iter()
+            .map(|s| (s.name.clone(), s.color.clone(), s.points.clone()))
+            .collect();
+        if let (Some(data), Some(enc)) = (&self.data, &self.encoding) {
+            let first = named.len();
+            let rows = transform::apply(data.values.clone(), &self.transform)?;
+            for (idx, row) in rows.iter().enumerate() {
+                let point = (number(row, idx, &enc.x.field)?, number(row, idx, &enc.y.field)?);
+                let name = label(enc.color.as_ref().and_then(|c| row.get(&c.field)));
+                match named[first..].iter_mut().find(|(n, _, _)| *n == name) {
+                    Some((_, _, points)) => points.push(point),
+                    None => named.push((name, None, vec![point])),
+                }
+            }
+        }
+        named
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (name, color, mut points))| {
+                if points.is_empty() {
+                    return Err(SpecError::EmptySeries(name));
+                }
+                points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+                Ok(ResolvedSeries {
+                    color: color.unwrap_or_else(|| self.palette(idx)),
+                    name,
+                    points,
+                })
+            })
+            .collect()
+    }
+
+    /// The domain the spec asks for, fitting unset axes to `series`.
+    pub fn domain(&self, series: &[ResolvedSeries]) -> Domain {
+        let fit = |spec: &ScaleSpec, pick: fn(&(f32, f32)) -> f32| {
+            if let Some(domain) = spec.domain {
+                return domain;
+            }
+            let values = series.iter().flat_map(|s| s.points.iter()).map(pick);
+            let (mut lo, mut hi) = values.fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+            if spec.zero {
+                lo = lo.min(0.0);
+                hi = hi.max(0.0);
+            }
+            if lo >= hi {
+                (lo - 1.0, lo + 1.0)
+            } else {
+                (lo, hi)
+            }
+        };
+        Domain::new(fit(&self.x.scale, |p| p.0), fit(&self.y.scale, |p| p.1))
+    }
+
+    /// The margins around the plot, with the right one widened to fit the
+    /// legend when it is placed there.
+    pub fn margins(&self, series: &[ResolvedSeries]) -> Margins {
+        let (top, right, bottom, left) = MARGINS;
+        if !self.legend.show || self.legend.position != LegendPosition::Right {
+            return MARGINS;
+        }
+        let widest = series
+            .iter()
+            .filter(|s| !s.name.is_empty())
+            .map(|s| render::legend_entry_width(&s.name))
+            .fold(0.0, f32::max);
+        // entries start 8 units into the margin
+        (top, right.max(8 + widest.ceil() as isize), bottom, left)
+    }
+
+    fn projection(&self, domain: Domain, (top, right, bottom, left): Margins) -> Projection {
+        Projection {
+            left: left as f32,
+            top: top as f32,
+            width: (self.width - left - right) as f32,
+            height: (self.height - top - bottom) as f32,
+            domain,
+        }
+    }
+
+    /// Validates the spec and draws it, the same way `ChartComponent` does.
+    pub fn build(&self) -> Result<Group, SpecError> {
+        self.validate()?;
+        let series = self.resolve()?;
+        let domain = self.domain(&series);
+        let (width, height) = (self.width, self.height);
+        let margins = self.margins(&series);
+        let (top, right, bottom, left) = margins;
+        if width <= left + right {
+            return Err(SpecError::TooSmall {
+                field: "width",
+                min: left + right,
+                value: width,
+            });
+        }
+        let x = charts::ScaleLinear::new()
+            .set_domain(vec![domain.x.0, domain.x.1])
+            .set_range(vec![0, width - left - right]);
+        let y = charts::ScaleLinear::new()
+            .set_domain(vec![domain.y.0, domain.y.1])
+            .set_range(vec![height - top - bottom, 0]);
+        let mut c = charts::Chart::new()
+            .set_width(width)
+            .set_height(height)
+            .set_margins(top, right, bottom, left);
+        if let Some(title) = &self.title {
+            c = c.add_title(title.clone());
+        }
+        c = c.add_axis_bottom(&x).add_axis_left(&y);
+        if let Some(label) = &self.x.label {
+            c = c.add_bottom_axis_label(label);
+        }
+        if let Some(label) = &self.y.label {
+            c = c.add_left_axis_label(label);
+        }
+        let group = c.to_svg().map_err(SpecError::Render)?;
+        let proj = self.projection(domain, margins);
+        Ok(group
+            .add(self.series_svg(&series, &proj))
+            .add(self.annotations_svg(&proj))
+            .add(self.legend_svg(&series, margins)))
+    }
+
+    /// The series, clipped to the plot. Points just outside the domain are
+    /// kept so lines leave the plot at its edge instead of stopping short.
+    fn series_svg(&self, series: &[ResolvedSeries], proj: &Projection) -> Group {
+        let id = format!("spec-clip-{}", SPECS.fetch_add(1, Ordering::Relaxed));
+        let clip = ClipPath::new().set("id", id.clone()).add(
+            Rectangle::new()
+                .set("x", proj.left)
+                .set("y", proj.top)
+                .set("width", proj.width)
+                .set("height", proj.height),
+        );
+        let group = Group::new()
+            .set("class", "series")
+            .set("clip-path", format!("url(#{})", id));
+        series.iter().fold(group, |g, s| {
+            let points: Vec<(f32, f32)> = s.points[proj.domain.visible(&s.points, |(x, _)| *x)]
+                .iter()
+                .map(|p| proj.to_pixel(*p))
+                .collect();
+            let markers = |g: Group| {
+                points.iter().fold(g, |g, (cx, cy)| {
+                    g.add(Circle::new().set("cx", *cx).set("cy", *cy).set("r", 3).set("fill", s.color.clone()))
+                })
+            };
+            let line = || {
+                Path::new()
+                    .set("d", curve::line_path(&points, Curve::Linear))
+                    .set("fill", "none")
+                    .set("stroke", s.color.clone())
+                    .set("stroke-width", 2)
+            };
+            let drawn = match self.kind {
+                ChartKind::Scatter => markers(Group::new().set("class", "scatter")),
+                ChartKind::Line => markers(Group::new().set("class", "line").add(line())),
+                ChartKind::Area => {
+                    let (_, zero) = proj.to_pixel((0.0, 0.0));
+                    let base: Vec<(f32, f32)> = points.iter().map(|(px, _)| (*px, zero)).collect();
+                    Group::new()
+                        .set("class", "area")
+                        .add(
+                            Path::new()
+                                .set("d", curve::area_path(&points, &base, Curve::Linear))
+                                .set("fill", s.color.clone())
+                                .set("fill-opacity", 0.3)
+                                .set("stroke", "none"),
+                        )
+                        .add(line())
+                }
+            };
+            g.add(drawn)
+        })
+        .add(Definitions::new().add(clip))
+    }
+
+    fn annotations_svg(&self, proj: &Projection) -> Group {
+        let (x0, y0) = (proj.left, proj.top);
+        let (x1, y1) = (proj.left + proj.width, proj.top + proj.height);
+        self.annotations
+            .iter()
+            .fold(Group::new().set("class", "annotations"), |g, a| match a {
+                Annotation::Rule { x, y, label, color } => {
+                    let color = color.clone().unwrap_or_else(|| "#555555".to_string());
+                    let ((ax, ay), (bx, by)) = match (x, y) {
+                        (Some(x), _) => {
+                            let (px, _) = proj.to_pixel((*x, proj.domain.y.0));
+                            ((px, y0), (px, y1))
+                        }
+                        (_, Some(y)) => {
+                            let (_, py) = proj.to_pixel((proj.domain.x.0, *y));
+                            ((x0, py), (x1, py))
+                        }
+                        _ => return g,
+                    };
+                    // rules outside the visible domain are skipped rather than drawn over the margins
+                    if ax < x0 || ax > x1 || ay < y0 || ay > y1 {
+                        return g;
+                    }
+                    let g = g.add(
+                        Line::new()
+                            .set("x1", ax)
+                            .set("y1", ay)
+                            .set("x2", bx)
+                            .set("y2", by)
+                            .set("stroke", color.clone())
+                            .set("stroke-dasharray", "4 2"),
+                    );
+                    match label {
+                        Some(label) => g.add(
+                            Text::new()
+                                .set("x", bx - 4.0)
+                                .set("y", ay - 4.0)
+                                .set("text-anchor", "end")
+                                .set("font-family", "sans-serif")
+                                .set("font-size", 12)
+                                .set("fill", color)
+                                .add(svg::node::Text::new(label.clone())),
+                        ),
+                        None => g,
+                    }
+                }
+                Annotation::Text { x, y, text, color } => {
+                    if !proj.domain.contains((*x, *y)) {
+                        return g;
+                    }
+                    let (px, py) = proj.to_pixel((*x, *y));
+                    g.add(
+                        Text::new()
+                            .set("x", px)
+                            .set("y", py)
+                            .set("font-family", "sans-serif")
+                            .set("font-size", 12)
+                            .set("fill", color.clone().unwrap_or_else(|| "#333333".to_string()))
+                            .add(svg::node::Text::new(text.clone())),
+                    )
+                }
+            })
+    }
+
+    fn legend_svg(&self, series: &[ResolvedSeries], (top, right, _, left): Margins) -> Group {
+        let legend = Group::new().set("class", "legend");
+        let named: Vec<&ResolvedSeries> = series.iter().filter(|s| !s.name.is_empty()).collect();
+        if !self.legend.show || named.is_empty() {
+            return legend;
+        }
+        let (mut x, mut y, vertical) = match self.legend.position {
+            LegendPosition::Top => (left as f32, top as f32 - 20.0, false),
+            LegendPosition::Bottom => (left as f32, self.height as f32 - 8.0, false),
+            LegendPosition::Right => (self.width as f32 - right as f32 + 8.0, top as f32 + 12.0, true),
+        };
+        named.into_iter().fold(legend, |g, s| {
+            let entry = Group::new()
+                .set("class", "legend-entry")
+                .add(
+                    Rectangle::new()
+                        .set("x", x)
+                        .set("y", y - 10.0)
+                        .set("width", 12)
+                        .set("height", 12)
+                        .set("fill", s.color.clone()),
+                )
+                .add(
+                    Text::new()
+                        .set("x", x + 16.0)
+                        .set("y", y)
+                        .set("font-family", "sans-serif")
+                        .set("font-size", 12)
+                        .add(svg::node::Text::new(s.name.clone())),
+                );
+            if vertical {
+                y += 18.0;
+            } else {
+                x += render::legend_entry_width(&s.name);
+            }
+            g.add(entry)
+        })
+    }
+
+    /// Renders the spec as a standalone svg document, for use outside a browser.
+    pub fn to_document(&self) -> Result<svg::Document, SpecError> {
+        let group = self.build()?;
+        Ok(svg::Document::new()
+            .set("width", self.width as i32)
+            .set("height", self.height as i32)
+            .set("viewBox", format!("0 0 {} {}", self.width, self.height))
+            .add(
+                Rectangle::new()
+                    .set("width", "100%")
+                    .set("height", "100%")
+                    .set("fill", "#FFFFFF"),
+            )
+            .add(group))
+    }
+
+    /// Renders the spec into yew nodes.
+    pub fn to_html(&self) -> Result<Html, SpecError> {
+        let group = self.build()?;
+        let root = render::svg_root(self.width, self.height);
+        Ok(render::svg_to_vtag(&group, root, |_, _| {}).into())
+    }
+}
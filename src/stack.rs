@@ -0,0 +1,147 @@
+//! Stacking series on top of each other, for stacked bars and areas.
+use plotters::prelude::*;
+
+use crate::series::Series;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stack {
+    /// Layer each series on the running total
+    Normal,
+    /// Like `Normal`, but scaled so each position adds up to 100
+    Percent,
+}
+
+/// Where one series sits at one position of a stack.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Segment {
+    /// The series' own value
+    pub value: f32,
+    /// Bottom and top of the segment on the value axis
+    pub from: f32,
+    pub to: f32,
+    /// Sum of every series' value at this position
+    pub total: f32,
+}
+
+/// Stacks `values[series][position]` in series order, so the first series is
+/// the bottom layer. Positive values stack up from zero and negative ones
+/// down from it; missing positions count as zero.
+pub fn stack(values: &[Vec<f32>], mode: Stack) -> Vec<Vec<Segment>> {
+    let positions = values.iter().map(Vec::len).max().unwrap_or(0);
+    let value = |s: usize, p: usize| values[s].get(p).cloned().filter(|v| v.is_finite()).unwrap_or(0.0);
+    let mut layers = vec![Vec::with_capacity(positions); values.len()];
+    for p in 0..positions {
+        let total: f32 = (0..values.len()).map(|s| value(s, p)).sum();
+        let scale = match mode {
+            Stack::Normal => 1.0,
+            Stack::Percent => {
+                let magnitude: f32 = (0..values.len()).map(|s| value(s, p).abs()).sum();
+                if magnitude > 0.0 {
+                    100.0 / magnitude
+                } else {
+                    0.0
+                }
+            }
+        };
+        let (mut up, mut down) = (0.0, 0.0);
+        for (s, layer) in layers.iter_mut().enumerate() {
+            let v = value(s, p);
+            let base = if v < 0.0 { &mut down } else { &mut up };
+            let from = *base;
+            *base += v * scale;
+            layer.push(Segment {
+                value: v,
+                from,
+                to: *base,
+                total,
+            });
+        }
+    }
+    layers
+}
+
+/// The value range a stack covers, always including zero.
+pub fn extent(layers: &[Vec<Segment>]) -> (f32, f32) {
+    let (lo, hi) = layers
+        .iter()
+        .flatten()
+        .fold((0.0f32, 0.0f32), |(lo, hi), s| (lo.min(s.from.min(s.to)), hi.max(s.from.max(s.to))));
+    match lo == hi {
+        true => (lo, lo + 1.0),
+        false => (lo, hi),
+    }
+}
+
+/// Puts `series` on the union of their x values so they can be stacked.
+/// Between its own samples a series is interpolated linearly; outside them
+/// it counts as zero.
+pub fn align<'a, I: IntoIterator<Item = &'a Series>>(series: I) -> (Vec<isize>, Vec<Vec<f32>>) {
+    let series: Vec<&Series> = series.into_iter().collect();
+    let mut xs: Vec<isize> = series.iter().flat_map(|s| s.data.iter().map(|(x, _)| *x)).collect();
+    xs.sort_unstable();
+    xs.dedup();
+    let values = series
+        .iter()
+        .map(|s| {
+            let mut data = s.data.clone();
+            data.sort_by_key(|(x, _)| *x);
+            xs.iter().map(|x| interpolate(&data, *x)).collect()
+        })
+        .collect();
+    (xs, values)
+}
+
+fn interpolate(data: &[(isize, isize)], x: isize) -> f32 {
+    let idx = match data.binary_search_by_key(&x, |(x, _)| *x) {
+        Ok(idx) => return data[idx].1 as f32,
+        Err(idx) => idx,
+    };
+    if idx == 0 || idx == data.len() {
+        return 0.0;
+    }
+    let ((x0, y0), (x1, y1)) = (data[idx - 1], data[idx]);
+    let t = (x - x0) as f32 / (x1 - x0) as f32;
+    y0 as f32 + (y1 - y0) as f32 * t
+}
+
+/// The closed outline of one layer over `xs`: along its tops, then back
+/// along its bottoms. Layers that change sign still fill between the two.
+pub fn outline(xs: &[f32], layer: &[Segment]) -> Vec<(f32, f32)> {
+    let top = xs.iter().zip(layer).map(|(x, s)| (*x, s.to));
+    let bottom = xs.iter().zip(layer).rev().map(|(x, s)| (*x, s.from));
+    top.chain(bottom).collect()
+}
+
+/// Polygons for stacked areas over `xs`, one per layer, for drawing with
+/// plotters' `ChartContext::draw_series` in a `PlotComponent`.
+pub fn area_polygons(xs: &[f32], layers: &[Vec<Segment>], colors: &[RGBColor]) -> Vec<Polygon<(f32, f32)>> {
+    layers
+        .iter()
+        .enumerate()
+        .map(|(idx, layer)| {
+            let color = &colors[idx % colors.len()];
+            Polygon::new(outline(xs, layer), color.filled())
+        })
+        .collect()
+}
+
+/// Rectangles for stacked bars centred on `xs` and `width` wide, for
+/// drawing with plotters like `area_polygons`.
+pub fn bar_rectangles(
+    xs: &[f32],
+    width: f32,
+    layers: &[Vec<Segment>],
+    colors: &[RGBColor],
+) -> Vec<Rectangle<(f32, f32)>> {
+    layers
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, layer)| {
+            let color = &colors[idx % colors.len()];
+            xs.iter()
+                .zip(layer)
+                .filter(|(_, s)| s.from != s.to)
+                .map(move |(x, s)| Rectangle::new([(x - width / 2.0, s.from), (x + width / 2.0, s.to)], color.filled()))
+        })
+        .collect()
+}
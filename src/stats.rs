@@ -0,0 +1,30 @@
+//! Summary statistics shared by the histogram, box plot, uncertainty band
+//! and rolling window helpers.
+use std::cmp::Ordering;
+
+/// The finite `samples` in ascending order.
+pub fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = samples.iter().cloned().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    sorted
+}
+
+/// The `p`th quantile (0 to 1) of `sorted` samples, linearly interpolated
+/// between them. `None` if there are no samples.
+pub fn quantile(sorted: &[f64], p: f64) -> Option<f64> {
+    let rank = p.clamp(0.0, 1.0) * sorted.len().checked_sub(1)? as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64))
+}
+
+/// The interquartile range of `sorted` samples.
+pub fn iqr(sorted: &[f64]) -> Option<f64> {
+    Some(quantile(sorted, 0.75)? - quantile(sorted, 0.25)?)
+}
+
+/// The sample standard deviation.
+pub fn std_dev(samples: &[f64]) -> f64 {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    (samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0)).sqrt()
+}
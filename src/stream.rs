@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::series::Series;
+
+/// How much history a stream keeps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Retention {
+    /// The latest `n` samples of each series
+    Count(usize),
+    /// Samples whose x is within this many units of the newest sample
+    Span(f32),
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention::Count(1000)
+    }
+}
+
+#[derive(Default)]
+struct Streams {
+    retention: Retention,
+    series: Vec<(String, VecDeque<(isize, isize)>)>,
+    version: u64,
+}
+
+impl Streams {
+    fn latest(&self) -> Option<isize> {
+        self.series
+            .iter()
+            .filter_map(|(_, buf)| buf.back().map(|(x, _)| *x))
+            .max()
+    }
+
+    fn trim(&mut self) {
+        match self.retention {
+            Retention::Count(n) => {
+                for (_, buf) in &mut self.series {
+                    while buf.len() > n {
+                        buf.pop_front();
+                    }
+                }
+            }
+            Retention::Span(span) => {
+                let latest = match self.latest() {
+                    Some(x) => x as f32,
+                    None => return,
+                };
+                for (_, buf) in &mut self.series {
+                    while buf.front().map(|(x, _)| (*x as f32) < latest - span).unwrap_or(false) {
+                        buf.pop_front();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A shared, cheaply cloned handle that live data is appended to.
+///
+/// Hand a clone to `ChartComponent`'s `stream` prop and keep another wherever
+/// samples arrive; the chart picks up new samples at most `max_fps` times a
+/// second instead of on every append.
+#[derive(Clone, Default)]
+pub struct StreamHandle(Rc<RefCell<Streams>>);
+
+impl PartialEq for StreamHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl StreamHandle {
+    pub fn new(retention: Retention) -> Self {
+        Self(Rc::new(RefCell::new(Streams {
+            retention,
+            ..Streams::default()
+        })))
+    }
+
+    pub fn push(&self, series: &str, point: (isize, isize)) {
+        self.extend(series, std::iter::once(point));
+    }
+
+    /// Appends samples to a series, creating it if needed. Samples are
+    /// expected in increasing x order.
+    pub fn extend<I: IntoIterator<Item = (isize, isize)>>(&self, series: &str, points: I) {
+        let mut streams = self.0.borrow_mut();
+        let idx = match streams.series.iter().position(|(name, _)| name == series) {
+            Some(idx) => idx,
+            None => {
+                streams.series.push((series.to_string(), VecDeque::new()));
+                streams.series.len() - 1
+            }
+        };
+        streams.series[idx].1.extend(points);
+        streams.trim();
+        streams.version += 1;
+    }
+
+    pub fn clear(&self) {
+        let mut streams = self.0.borrow_mut();
+        streams.series.clear();
+        streams.version += 1;
+    }
+
+    /// Bumped on every change, so readers can skip unchanged frames.
+    pub fn version(&self) -> u64 {
+        self.0.borrow().version
+    }
+
+    pub fn snapshot(&self) -> Vec<Series> {
+        self.0
+            .borrow()
+            .series
+            .iter()
+            .map(|(name, buf)| Series::new(name.clone(), buf.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// The x range the retention window covers, for scrolling the chart along.
+    pub fn x_extent(&self) -> Option<(f32, f32)> {
+        let streams = self.0.borrow();
+        let latest = streams.latest()? as f32;
+        match streams.retention {
+            Retention::Span(span) => Some((latest - span, latest)),
+            Retention::Count(_) => {
+                let first = streams
+                    .series
+                    .iter()
+                    .filter_map(|(_, buf)| buf.front().map(|(x, _)| *x))
+                    .min()? as f32;
+                Some((first, latest.max(first + 1.0)))
+            }
+        }
+    }
+}
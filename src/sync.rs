@@ -0,0 +1,76 @@
+use std::collections::{HashMap, HashSet};
+use yew::agent::{Agent, AgentLink, Context, HandlerId};
+
+/// What charts in the same sync group share with each other.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncEvent {
+    /// The data x under the pointer, or `None` once it leaves the chart
+    Cursor(Option<f32>),
+    /// The visible x range after a zoom or pan; each chart keeps its own y range
+    XRange((f32, f32)),
+}
+
+pub enum SyncInput {
+    Join(String),
+    Publish(SyncEvent),
+}
+
+/// Relays cursor and view changes between charts that joined the same group.
+pub struct SyncAgent {
+    link: AgentLink<Self>,
+    groups: HashMap<String, HashSet<HandlerId>>,
+    members: HashMap<HandlerId, String>,
+}
+
+impl SyncAgent {
+    fn leave(&mut self, id: HandlerId) {
+        if let Some(group) = self.members.remove(&id) {
+            if let Some(ids) = self.groups.get_mut(&group) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.groups.remove(&group);
+                }
+            }
+        }
+    }
+}
+
+impl Agent for SyncAgent {
+    type Reach = Context<Self>;
+    type Message = ();
+    type Input = SyncInput;
+    type Output = SyncEvent;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self {
+            link,
+            groups: HashMap::new(),
+            members: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            SyncInput::Join(group) => {
+                self.leave(id);
+                self.groups.entry(group.clone()).or_default().insert(id);
+                self.members.insert(id, group);
+            }
+            SyncInput::Publish(event) => {
+                let peers = self
+                    .members
+                    .get(&id)
+                    .and_then(|group| self.groups.get(group));
+                for peer in peers.into_iter().flatten().filter(|peer| **peer != id) {
+                    self.link.respond(*peer, event.clone());
+                }
+            }
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.leave(id);
+    }
+}
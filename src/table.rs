@@ -0,0 +1,487 @@
+use std::rc::Rc;
+use thiserror::Error;
+
+use crate::fetch::MapFn;
+use crate::scatter::ScatterPoint;
+use crate::series::Series;
+use crate::spec::Row;
+
+#[derive(Error, Debug)]
+pub enum TableError {
+    #[error("line {line}: {reason}")]
+    Line { line: usize, reason: String },
+    #[error("no column named `{0}`")]
+    NoColumn(String),
+    #[error("column `{0}` is not numeric")]
+    NotNumeric(String),
+    #[error("no rows to read")]
+    Empty,
+    #[error("failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// How to read delimited text. Anything left as `None` is guessed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    /// `,` or `\t`; picked from whichever occurs more in the first line
+    pub delimiter: Option<char>,
+    /// Whether the first row holds column names
+    pub header: Option<bool>,
+}
+
+impl Options {
+    pub fn csv() -> Self {
+        Self {
+            delimiter: Some(','),
+            ..Self::default()
+        }
+    }
+
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: Some('\t'),
+            ..Self::default()
+        }
+    }
+}
+
+/// A column with the type inferred from all of its non-empty cells.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Number(Vec<Option<f64>>),
+    /// Unix seconds, parsed from RFC 3339 style dates and times
+    Time(Vec<Option<f64>>),
+    Text(Vec<String>),
+}
+
+impl Column {
+    fn infer(cells: Vec<String>) -> Self {
+        let filled = || cells.iter().map(|c| c.trim()).filter(|c| !c.is_empty());
+        if filled().all(|c| parse_number(c).is_some()) {
+            Column::Number(cells.iter().map(|c| parse_number(c.trim())).collect())
+        } else if filled().all(|c| parse_timestamp(c).is_some()) {
+            Column::Time(cells.iter().map(|c| parse_timestamp(c.trim())).collect())
+        } else {
+            Column::Text(cells)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Number(v) | Column::Time(v) => v.len(),
+            Column::Text(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, Column::Text(_))
+    }
+
+    /// The cell as a number, if the column is numeric and the cell isn't empty.
+    pub fn number(&self, row: usize) -> Option<f64> {
+        match self {
+            Column::Number(v) | Column::Time(v) => v.get(row).cloned().flatten(),
+            Column::Text(_) => None,
+        }
+    }
+
+    /// The cell as text, e.g. for naming series.
+    pub fn text(&self, row: usize) -> String {
+        match self {
+            Column::Text(v) => v.get(row).cloned().unwrap_or_default(),
+            _ => self.number(row).map(|n| n.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Which columns become x, y and the series split.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub x: String,
+    pub y: String,
+    /// One series per distinct value of this column
+    pub split: Option<String>,
+}
+
+impl Selection {
+    pub fn new<X: Into<String>, Y: Into<String>>(x: X, y: Y) -> Self {
+        Self {
+            x: x.into(),
+            y: y.into(),
+            split: None,
+        }
+    }
+
+    pub fn split_by<S: Into<String>>(mut self, column: S) -> Self {
+        self.split = Some(column.into());
+        self
+    }
+
+    /// A `FetchChart` mapper for endpoints that serve csv or tsv.
+    pub fn map_fn(self, options: Options) -> MapFn {
+        Rc::new(move |body: &str| {
+            Table::parse(body, &options)
+                .and_then(|table| table.series(&self))
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Delimited text read into named, typed columns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub names: Vec<String>,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn parse(text: &str, options: &Options) -> Result<Self, TableError> {
+        let delimiter = options.delimiter.unwrap_or_else(|| sniff_delimiter(text));
+        let mut records = split_records(text, delimiter)?;
+        let (first_line, first) = match records.first() {
+            Some(record) => record.clone(),
+            None => return Err(TableError::Empty),
+        };
+        let width = first.len();
+        for (line, record) in &records {
+            if record.len() != width {
+                return Err(TableError::Line {
+                    line: *line,
+                    reason: format!("expected {} fields, found {}", width, record.len()),
+                });
+            }
+        }
+        let header = options.header.unwrap_or_else(|| {
+            first
+                .iter()
+                .map(|c| c.trim())
+                .all(|c| !c.is_empty() && parse_number(c).is_none() && parse_timestamp(c).is_none())
+        });
+        let names = match header {
+            true => {
+                records.remove(0);
+                first.iter().map(|c| c.trim().to_string()).collect()
+            }
+            false => (1..=width).map(|i| format!("column{}", i)).collect(),
+        };
+        if records.is_empty() {
+            return Err(TableError::Line {
+                line: first_line,
+                reason: "header has no rows after it".to_string(),
+            });
+        }
+        let mut cells: Vec<Vec<String>> = vec![Vec::with_capacity(records.len()); width];
+        for (_, record) in records {
+            for (column, cell) in cells.iter_mut().zip(record) {
+                column.push(cell);
+            }
+        }
+        Ok(Self {
+            names,
+            columns: cells.into_iter().map(Column::infer).collect(),
+        })
+    }
+
+    /// Reads a file, taking the delimiter from a `.tsv` or `.csv` extension.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self, TableError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let options = match path.extension().and_then(|e| e.to_str()) {
+            Some("tsv") | Some("tab") => Options::tsv(),
+            Some("csv") => Options::csv(),
+            _ => Options::default(),
+        };
+        Self::parse(&text, &options)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.columns.first().map(Column::len).unwrap_or(0)
+    }
+
+    pub fn column(&self, name: &str) -> Result<&Column, TableError> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|idx| &self.columns[idx])
+            .ok_or_else(|| TableError::NoColumn(name.to_string()))
+    }
+
+    fn numeric(&self, name: &str) -> Result<&Column, TableError> {
+        let column = self.column(name)?;
+        match column.is_numeric() {
+            true => Ok(column),
+            false => Err(TableError::NotNumeric(name.to_string())),
+        }
+    }
+
+    /// `(x, y)` pairs for `ChartComponent`'s `data` prop, skipping rows with
+    /// an empty cell in either column.
+    pub fn points(&self, x: &str, y: &str) -> Result<Vec<(isize, isize)>, TableError> {
+        let (xs, ys) = (self.numeric(x)?, self.numeric(y)?);
+        Ok((0..self.rows())
+            .filter_map(|row| Some((xs.number(row)?, ys.number(row)?)))
+            .map(|(x, y)| (x.round() as isize, y.round() as isize))
+            .collect())
+    }
+
+    /// Series for `ChartComponent`'s `series` prop, split by `selection.split`
+    /// in order of first appearance. Each series is sorted by x.
+    pub fn series(&self, selection: &Selection) -> Result<Vec<Series>, TableError> {
+        let (xs, ys) = (self.numeric(&selection.x)?, self.numeric(&selection.y)?);
+        let split = match &selection.split {
+            Some(name) => Some(self.column(name)?),
+            None => None,
+        };
+        let mut series: Vec<Series> = Vec::new();
+        for row in 0..self.rows() {
+            let point = match (xs.number(row), ys.number(row)) {
+                (Some(x), Some(y)) => (x.round() as isize, y.round() as isize),
+                _ => continue,
+            };
+            let name = match split {
+                Some(column) => column.text(row),
+                None => selection.y.clone(),
+            };
+            match series.iter_mut().find(|s| s.name == name) {
+                Some(s) => s.data.push(point),
+                None => series.push(Series::new(name, vec![point])),
+            }
+        }
+        for s in &mut series {
+            s.data.sort_by_key(|(x, _)| *x);
+        }
+        Ok(series)
+    }
+
+    /// Points for `ScatterChart`, with radius and color taken from the
+    /// optional `size` and `color` columns. Rows without x or y are skipped.
+    pub fn scatter_points(
+        &self,
+        x: &str,
+        y: &str,
+        size: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<Vec<ScatterPoint>, TableError> {
+        let (xs, ys) = (self.numeric(x)?, self.numeric(y)?);
+        let size = size.map(|name| self.numeric(name)).transpose()?;
+        let color = color.map(|name| self.numeric(name)).transpose()?;
+        Ok((0..self.rows())
+            .filter_map(|row| {
+                Some(ScatterPoint {
+                    x: xs.number(row)? as f32,
+                    y: ys.number(row)? as f32,
+                    size: size.and_then(|c| c.number(row)).map(|v| v as f32),
+                    color: color.and_then(|c| c.number(row)).map(|v| v as f32),
+                    label: None,
+                    bounds: None,
+                })
+            })
+            .collect())
+    }
+
+    /// The table as records, for chart specs' `data.values`. Times become
+    /// unix seconds.
+    pub fn records(&self) -> Vec<Row> {
+        (0..self.rows())
+            .map(|row| {
+                self.names
+                    .iter()
+                    .zip(&self.columns)
+                    .map(|(name, column)| {
+                        let value = match column {
+                            Column::Text(v) => serde_json::Value::String(v[row].clone()),
+                            _ => column
+                                .number(row)
+                                .and_then(serde_json::Number::from_f64)
+                                .map(serde_json::Value::Number)
+                                .unwrap_or(serde_json::Value::Null),
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn sniff_delimiter(text: &str) -> char {
+    let first = text.lines().next().unwrap_or("");
+    match first.matches('\t').count() > first.matches(',').count() {
+        true => '\t',
+        false => ',',
+    }
+}
+
+/// Splits text into `(line, fields)` records, honouring double quotes and
+/// `""` escapes. Quoted fields may span lines; blank lines are skipped.
+fn split_records(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, TableError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            '\n' if quoted => {
+                line += 1;
+                field.push('\n');
+            }
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                if !(fields.len() == 1 && fields[0].trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(TableError::Line {
+            line: start,
+            reason: "unterminated quoted field".to_string(),
+        });
+    }
+    fields.push(field);
+    if !(fields.len() == 1 && fields[0].trim().is_empty()) {
+        records.push((start, fields));
+    }
+    Ok(records)
+}
+
+fn parse_number(cell: &str) -> Option<f64> {
+    cell.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Parses `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fff]]` (or a
+/// space instead of `T`) and a `Z` or `±HH:MM` offset. Times without an
+/// offset are taken as UTC.
+pub fn parse_timestamp(cell: &str) -> Option<f64> {
+    let s = cell.as_bytes();
+    let digits = |from: usize, len: usize| -> Option<i64> {
+        let part = s.get(from..from + len)?;
+        if !part.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(part).ok()?.parse().ok()
+    };
+    let expect = |at: usize, c: u8| s.get(at) == Some(&c);
+    if !(expect(4, b'-') && expect(7, b'-')) {
+        return None;
+    }
+    let (year, month, day) = (digits(0, 4)?, digits(5, 2)?, digits(8, 2)?);
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+    let mut seconds = (days_from_civil(year, month, day) * 86_400) as f64;
+    if s.len() == 10 {
+        return Some(seconds);
+    }
+    if !(expect(10, b'T') || expect(10, b' ')) || !expect(13, b':') {
+        return None;
+    }
+    let (hour, minute) = (digits(11, 2)?, digits(14, 2)?);
+    let mut at = 16;
+    let mut second = 0.0;
+    if expect(at, b':') {
+        second = digits(at + 1, 2)? as f64;
+        at += 3;
+        if expect(at, b'.') {
+            let len = s[at + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            second += std::str::from_utf8(&s[at..at + 1 + len]).ok()?.parse::<f64>().ok()?;
+            at += 1 + len;
+        }
+    }
+    if hour > 23 || minute > 59 || second >= 61.0 {
+        return None;
+    }
+    seconds += (hour * 3600 + minute * 60) as f64 + second;
+    match s.get(at) {
+        None => Some(seconds),
+        Some(b'Z') | Some(b'z') if s.len() == at + 1 => Some(seconds),
+        Some(sign @ b'+') | Some(sign @ b'-') => {
+            let oh = digits(at + 1, 2)?;
+            let om = match s.len() - at {
+                3 => 0,
+                5 => digits(at + 3, 2)?,
+                6 if expect(at + 3, b':') => digits(at + 4, 2)?,
+                _ => return None,
+            };
+            let offset = (oh * 3600 + om * 60) as f64;
+            Some(if *sign == b'+' { seconds - offset } else { seconds + offset })
+        }
+        _ => None,
+    }
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD` in UTC, followed by
+/// ` HH:MM` when `with_time` is set.
+pub fn format_timestamp(secs: f64, with_time: bool) -> String {
+    let secs = secs.floor() as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    if with_time {
+        format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60)
+    } else {
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// days since 1970-01-01 in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// the inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
@@ -0,0 +1,286 @@
+//! Resampling, rolling windows and alignment for time series.
+//!
+//! Everything works on `(x, y)` points sorted by x, where x is usually unix
+//! seconds. The `*_series` helpers do the same for `Series` so the results
+//! can go straight to a `ChartComponent`.
+use std::cmp::Ordering;
+
+use thiserror::Error;
+
+use crate::series::Series;
+use crate::stats;
+
+/// The most grid points `align` will build; far more than a chart can show.
+pub const MAX_STEPS: usize = 100_000;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum AlignError {
+    #[error("aligning would take {steps} intervals, more than the {} allowed", MAX_STEPS)]
+    TooManySteps { steps: f64 },
+}
+
+/// How the samples falling into one interval are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reducer {
+    Mean,
+    Sum,
+    Min,
+    Max,
+    Last,
+    /// Increase per unit of x of a counter, measured from the last sample of
+    /// the previous interval. A drop is taken as a counter reset.
+    Rate,
+}
+
+/// How much history a rolling statistic looks back over.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    /// The latest `n` samples, including the current one
+    Count(usize),
+    /// Samples whose x is within this many units before the current one
+    Span(f64),
+}
+
+/// What an aligned series holds at grid points it has no samples for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fill {
+    /// Leave a gap
+    Gap,
+    Zero,
+    /// Carry the last value forward
+    Previous,
+    /// Interpolate between the neighbouring values; gaps at the ends stay gaps
+    Linear,
+}
+
+fn by_x(a: &(f64, f64), b: &(f64, f64)) -> Ordering {
+    a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal)
+}
+
+fn bucket(x: f64, interval: f64, anchor: f64) -> f64 {
+    ((x - anchor) / interval).floor() * interval + anchor
+}
+
+/// Buckets `points` into intervals of `interval` starting at `anchor` and
+/// reduces each one to a single point at the start of its interval. Empty
+/// intervals are left out; `align` fills them in. Points with a non-finite x
+/// are dropped.
+pub fn resample(points: &[(f64, f64)], interval: f64, anchor: f64, reducer: Reducer) -> Vec<(f64, f64)> {
+    if interval.is_nan() || interval <= 0.0 {
+        return Vec::new();
+    }
+    let mut points: Vec<(f64, f64)> = points.iter().cloned().filter(|(x, _)| x.is_finite()).collect();
+    points.sort_by(by_x);
+    let mut out = Vec::new();
+    let mut previous: Option<(f64, f64)> = None;
+    let mut start = 0;
+    while start < points.len() {
+        let key = bucket(points[start].0, interval, anchor);
+        let end = start
+            + points[start..]
+                .iter()
+                .take_while(|(x, _)| bucket(*x, interval, anchor) == key)
+                .count();
+        let group = &points[start..end];
+        let ys = group.iter().map(|(_, y)| *y);
+        let value = match reducer {
+            Reducer::Mean => Some(ys.sum::<f64>() / group.len() as f64),
+            Reducer::Sum => Some(ys.sum()),
+            Reducer::Min => Some(ys.fold(f64::INFINITY, f64::min)),
+            Reducer::Max => Some(ys.fold(f64::NEG_INFINITY, f64::max)),
+            Reducer::Last => Some(group[group.len() - 1].1),
+            Reducer::Rate => rate(previous, group),
+        };
+        if let Some(value) = value {
+            out.push((key, value));
+        }
+        previous = Some(group[group.len() - 1]);
+        start = end;
+    }
+    out
+}
+
+fn rate(previous: Option<(f64, f64)>, group: &[(f64, f64)]) -> Option<f64> {
+    let samples: Vec<(f64, f64)> = previous.into_iter().chain(group.iter().cloned()).collect();
+    let (first, last) = (samples.first()?, samples.last()?);
+    let elapsed = last.0 - first.0;
+    if elapsed <= 0.0 {
+        return None;
+    }
+    let increase: f64 = samples
+        .windows(2)
+        .map(|w| match w[1].1 >= w[0].1 {
+            true => w[1].1 - w[0].1,
+            false => w[1].1,
+        })
+        .sum();
+    Some(increase / elapsed)
+}
+
+fn window_start(points: &[(f64, f64)], idx: usize, window: Window) -> usize {
+    match window {
+        Window::Count(n) => (idx + 1).saturating_sub(n.max(1)),
+        Window::Span(span) => {
+            let from = points[idx].0 - span;
+            points[..=idx].iter().position(|(x, _)| *x >= from).unwrap_or(idx)
+        }
+    }
+}
+
+/// The mean of each point's trailing window.
+pub fn rolling_mean(points: &[(f64, f64)], window: Window) -> Vec<(f64, f64)> {
+    let mut sum = 0.0;
+    let mut start = 0;
+    points
+        .iter()
+        .enumerate()
+        .map(|(idx, (x, y))| {
+            sum += y;
+            let from = window_start(points, idx, window);
+            while start < from {
+                sum -= points[start].1;
+                start += 1;
+            }
+            (*x, sum / (idx + 1 - start) as f64)
+        })
+        .collect()
+}
+
+/// The `p`th percentile (0 to 100) of each point's trailing window, linearly
+/// interpolated between samples.
+pub fn rolling_percentile(points: &[(f64, f64)], window: Window, p: f64) -> Vec<(f64, f64)> {
+    let p = p.max(0.0).min(100.0) / 100.0;
+    points
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (x, _))| {
+            let mut ys: Vec<f64> = points[window_start(points, idx, window)..=idx]
+                .iter()
+                .map(|(_, y)| *y)
+                .collect();
+            ys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            stats::quantile(&ys, p).map(|q| (*x, q))
+        })
+        .collect()
+}
+
+/// The shared grid and one column of values per series.
+pub type Aligned = (Vec<f64>, Vec<Vec<Option<f64>>>);
+
+/// Resamples every series onto one grid covering all of them, so values at
+/// the same index belong to the same interval. Fails rather than build a
+/// grid of more than `MAX_STEPS` points.
+pub fn align(
+    series: &[Vec<(f64, f64)>],
+    interval: f64,
+    reducer: Reducer,
+    fill: Fill,
+) -> Result<Aligned, AlignError> {
+    let xs = series.iter().flat_map(|s| s.iter().map(|(x, _)| *x)).filter(|x| x.is_finite());
+    let (lo, hi) = xs.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    if interval.is_nan() || interval <= 0.0 || lo > hi {
+        return Ok((Vec::new(), series.iter().map(|_| Vec::new()).collect()));
+    }
+    let anchor = bucket(lo, interval, 0.0);
+    let steps = ((hi - anchor) / interval).floor() + 1.0;
+    if steps > MAX_STEPS as f64 {
+        return Err(AlignError::TooManySteps { steps });
+    }
+    let steps = steps as usize;
+    let grid: Vec<f64> = (0..steps).map(|i| anchor + i as f64 * interval).collect();
+    let columns = series
+        .iter()
+        .map(|points| {
+            let mut column = vec![None; steps];
+            for (x, y) in resample(points, interval, anchor, reducer) {
+                let idx = ((x - anchor) / interval).round() as usize;
+                if let Some(slot) = column.get_mut(idx) {
+                    *slot = Some(y);
+                }
+            }
+            fill_gaps(&mut column, fill);
+            column
+        })
+        .collect();
+    Ok((grid, columns))
+}
+
+fn fill_gaps(column: &mut [Option<f64>], fill: Fill) {
+    match fill {
+        Fill::Gap => {}
+        Fill::Zero => column.iter_mut().filter(|v| v.is_none()).for_each(|v| *v = Some(0.0)),
+        Fill::Previous => {
+            let mut last = None;
+            for value in column.iter_mut() {
+                match value {
+                    Some(v) => last = Some(*v),
+                    None => *value = last,
+                }
+            }
+        }
+        Fill::Linear => {
+            let known: Vec<usize> = (0..column.len()).filter(|i| column[*i].is_some()).collect();
+            for pair in known.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let (ya, yb) = (column[a].unwrap_or(0.0), column[b].unwrap_or(0.0));
+                for i in a + 1..b {
+                    column[i] = Some(ya + (yb - ya) * (i - a) as f64 / (b - a) as f64);
+                }
+            }
+        }
+    }
+}
+
+fn points(series: &Series) -> Vec<(f64, f64)> {
+    series.data.iter().map(|(x, y)| (*x as f64, *y as f64)).collect()
+}
+
+fn with_points(series: &Series, points: Vec<(f64, f64)>) -> Series {
+    Series {
+        data: points
+            .into_iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|(x, y)| (x.round() as isize, y.round() as isize))
+            .collect(),
+        // bounds belonged to the old points
+        bounds: Vec::new(),
+        ..series.clone()
+    }
+}
+
+/// `resample` for a chart series, keeping its name and color.
+pub fn resample_series(series: &Series, interval: isize, reducer: Reducer) -> Series {
+    with_points(series, resample(&points(series), interval as f64, 0.0, reducer))
+}
+
+pub fn rolling_mean_series(series: &Series, window: Window) -> Series {
+    with_points(series, rolling_mean(&points(series), window))
+}
+
+pub fn rolling_percentile_series(series: &Series, window: Window, p: f64) -> Series {
+    with_points(series, rolling_percentile(&points(series), window, p))
+}
+
+/// `align` for chart series. Gaps left by `Fill::Gap` are dropped, so the
+/// line is drawn straight across them.
+pub fn align_series(
+    series: &[Series],
+    interval: isize,
+    reducer: Reducer,
+    fill: Fill,
+) -> Result<Vec<Series>, AlignError> {
+    let all: Vec<Vec<(f64, f64)>> = series.iter().map(points).collect();
+    let (grid, columns) = align(&all, interval as f64, reducer, fill)?;
+    Ok(series
+        .iter()
+        .zip(columns)
+        .map(|(s, column)| {
+            let aligned = grid
+                .iter()
+                .zip(column)
+                .filter_map(|(x, y)| Some((*x, y?)))
+                .collect();
+            with_points(s, aligned)
+        })
+        .collect())
+}
@@ -0,0 +1,411 @@
+//! Row transforms applied before charting, in the spirit of Vega's.
+//!
+//! In a spec they are listed under `transform` and run in order:
+//!
+//! ```json
+//! [
+//!   {"filter": {"field": "status", "op": "ge", "value": 500}},
+//!   {"bin": {"field": "t", "step": 60, "as": "minute"}},
+//!   {"aggregate": {"groupby": ["minute", "host"], "fields": [{"op": "count", "as": "errors"}]}},
+//!   {"sort": [{"field": "errors", "order": "descending"}]},
+//!   {"limit": 10}
+//! ]
+//! ```
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use thiserror::Error;
+
+use crate::spec::Row;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TransformError {
+    #[error("row {row}: field `{field}` is not a number")]
+    NotNumeric { row: usize, field: String },
+    #[error("`{op}` needs a `field`")]
+    MissingField { op: &'static str },
+    #[error("bin step must be positive, got {0}")]
+    BadStep(f64),
+    #[error("aggregate op `{0:?}` doesn't reduce numbers")]
+    NotReducing(AggregateOp),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transform {
+    /// Keeps rows matching the predicate
+    Filter(Predicate),
+    /// Collapses rows sharing the `groupby` fields into one row per group
+    Aggregate(Aggregate),
+    /// Adds the start of the fixed-width bin a numeric field falls in
+    Bin(Bin),
+    Sort(Vec<SortField>),
+    /// Keeps the first `n` rows
+    Limit(usize),
+    /// Adds a field computed from two operands
+    Calculate(Calculate),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// The field equals one of the values in an array
+    In,
+    /// The field is present and not null
+    Valid,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Predicate {
+    And { and: Vec<Predicate> },
+    Or { or: Vec<Predicate> },
+    Not { not: Box<Predicate> },
+    Field {
+        field: String,
+        op: CompareOp,
+        #[serde(default)]
+        value: Value,
+    },
+}
+
+impl Predicate {
+    pub fn matches(&self, row: &Row) -> bool {
+        match self {
+            Predicate::And { and } => and.iter().all(|p| p.matches(row)),
+            Predicate::Or { or } => or.iter().any(|p| p.matches(row)),
+            Predicate::Not { not } => !not.matches(row),
+            Predicate::Field { field, op, value } => {
+                let cell = row.get(field).unwrap_or(&Value::Null);
+                let ord = match comparable(cell, value) {
+                    true => Some(compare(cell, value)),
+                    false => None,
+                };
+                match op {
+                    CompareOp::Valid => !cell.is_null(),
+                    CompareOp::In => value
+                        .as_array()
+                        .map(|values| values.iter().any(|v| same(cell, v)))
+                        .unwrap_or(false),
+                    CompareOp::Eq => same(cell, value),
+                    CompareOp::Ne => !same(cell, value),
+                    CompareOp::Lt => ord == Some(Ordering::Less),
+                    CompareOp::Le => ord.map(|o| o != Ordering::Greater).unwrap_or(false),
+                    CompareOp::Gt => ord == Some(Ordering::Greater),
+                    CompareOp::Ge => ord.map(|o| o != Ordering::Less).unwrap_or(false),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateOp {
+    /// Rows in the group
+    Count,
+    /// Rows where the field is present and not null
+    Valid,
+    Distinct,
+    Sum,
+    #[serde(alias = "average")]
+    Mean,
+    Median,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AggregateField {
+    pub op: AggregateOp,
+    #[serde(default)]
+    pub field: Option<String>,
+    #[serde(rename = "as")]
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Aggregate {
+    #[serde(default)]
+    pub groupby: Vec<String>,
+    pub fields: Vec<AggregateField>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Bin {
+    pub field: String,
+    pub step: f64,
+    /// A value that falls on a bin boundary
+    #[serde(default)]
+    pub anchor: f64,
+    #[serde(rename = "as")]
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Ascending
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct SortField {
+    pub field: String,
+    #[serde(default)]
+    pub order: Order,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Operand {
+    Field { field: String },
+    Constant(f64),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Calculate {
+    pub op: Arithmetic,
+    pub left: Operand,
+    pub right: Operand,
+    #[serde(rename = "as")]
+    pub name: String,
+}
+
+// values of different json types only compare equal, never ordered
+fn comparable(a: &Value, b: &Value) -> bool {
+    matches!(
+        (a, b),
+        (Value::Number(_), Value::Number(_)) | (Value::String(_), Value::String(_)) | (Value::Bool(_), Value::Bool(_))
+    )
+}
+
+fn same(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+/// A total order over json values: null < bools < numbers < strings < the rest.
+pub fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(_), Value::Number(_)) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn number(row: &Row, idx: usize, field: &str) -> Result<Option<f64>, TransformError> {
+    match row.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v.as_f64().map(Some).ok_or_else(|| TransformError::NotNumeric {
+            row: idx,
+            field: field.to_string(),
+        }),
+    }
+}
+
+fn to_value(n: f64) -> Value {
+    serde_json::Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null)
+}
+
+/// Rows paired with their index in the input, so errors point at the row
+/// the user wrote even after filtering or sorting.
+type Numbered = Vec<(usize, Row)>;
+
+impl Aggregate {
+    fn apply(&self, rows: Numbered) -> Result<Numbered, TransformError> {
+        // groups keep the order their first row appeared in
+        let mut groups: Vec<(Vec<Value>, Numbered)> = Vec::new();
+        for (idx, row) in rows {
+            let key: Vec<Value> = self
+                .groupby
+                .iter()
+                .map(|f| row.get(f).cloned().unwrap_or(Value::Null))
+                .collect();
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, members)) => members.push((idx, row)),
+                None => groups.push((key, vec![(idx, row)])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(key, members)| {
+                let mut out: Row = self.groupby.iter().cloned().zip(key).collect();
+                for agg in &self.fields {
+                    out.insert(agg.name.clone(), agg.compute(&members)?);
+                }
+                // a group stands for the row that started it
+                Ok((members[0].0, out))
+            })
+            .collect()
+    }
+}
+
+impl AggregateField {
+    fn compute(&self, members: &[(usize, Row)]) -> Result<Value, TransformError> {
+        if self.op == AggregateOp::Count {
+            return Ok(Value::from(members.len()));
+        }
+        let field = self.field.as_deref().ok_or(TransformError::MissingField {
+            op: "aggregate",
+        })?;
+        let present = members
+            .iter()
+            .filter_map(|(_, row)| row.get(field).filter(|v| !v.is_null()));
+        match self.op {
+            AggregateOp::Valid => return Ok(Value::from(present.count())),
+            AggregateOp::Distinct => {
+                let mut seen: Vec<&Value> = Vec::new();
+                for v in present {
+                    if !seen.contains(&v) {
+                        seen.push(v);
+                    }
+                }
+                return Ok(Value::from(seen.len()));
+            }
+            _ => {}
+        }
+        let mut values = Vec::with_capacity(members.len());
+        for (idx, row) in members {
+            if let Some(v) = number(row, *idx, field)? {
+                values.push(v);
+            }
+        }
+        if values.is_empty() {
+            return Ok(match self.op {
+                AggregateOp::Sum => Value::from(0),
+                _ => Value::Null,
+            });
+        }
+        let n = values.len() as f64;
+        let result = match self.op {
+            AggregateOp::Sum => values.iter().sum(),
+            AggregateOp::Mean => values.iter().sum::<f64>() / n,
+            AggregateOp::Min => values.iter().cloned().fold(f64::INFINITY, f64::min),
+            AggregateOp::Max => values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            AggregateOp::Median => {
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                let mid = values.len() / 2;
+                match values.len() % 2 {
+                    0 => (values[mid - 1] + values[mid]) / 2.0,
+                    _ => values[mid],
+                }
+            }
+            op => return Err(TransformError::NotReducing(op)),
+        };
+        Ok(to_value(result))
+    }
+}
+
+impl Operand {
+    fn value(&self, row: &Row, idx: usize) -> Result<Option<f64>, TransformError> {
+        match self {
+            Operand::Field { field } => number(row, idx, field),
+            Operand::Constant(n) => Ok(Some(*n)),
+        }
+    }
+}
+
+impl Transform {
+    pub fn apply(&self, rows: Vec<Row>) -> Result<Vec<Row>, TransformError> {
+        let rows = self.apply_numbered(rows.into_iter().enumerate().collect())?;
+        Ok(rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    fn apply_numbered(&self, mut rows: Numbered) -> Result<Numbered, TransformError> {
+        match self {
+            Transform::Filter(predicate) => rows.retain(|(_, row)| predicate.matches(row)),
+            Transform::Aggregate(aggregate) => return aggregate.apply(rows),
+            Transform::Bin(bin) => {
+                if bin.step.is_nan() || bin.step <= 0.0 {
+                    return Err(TransformError::BadStep(bin.step));
+                }
+                for (idx, row) in rows.iter_mut() {
+                    let start = number(row, *idx, &bin.field)?
+                        .map(|v| ((v - bin.anchor) / bin.step).floor() * bin.step + bin.anchor);
+                    row.insert(bin.name.clone(), start.map(to_value).unwrap_or(Value::Null));
+                }
+            }
+            Transform::Sort(fields) => rows.sort_by(|(_, a), (_, b)| {
+                fields
+                    .iter()
+                    .map(|f| {
+                        let ord = compare(
+                            a.get(&f.field).unwrap_or(&Value::Null),
+                            b.get(&f.field).unwrap_or(&Value::Null),
+                        );
+                        match f.order {
+                            Order::Ascending => ord,
+                            Order::Descending => ord.reverse(),
+                        }
+                    })
+                    .find(|ord| *ord != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            }),
+            Transform::Limit(n) => rows.truncate(*n),
+            Transform::Calculate(calc) => {
+                for (idx, row) in rows.iter_mut() {
+                    let value = match (calc.left.value(row, *idx)?, calc.right.value(row, *idx)?) {
+                        (Some(l), Some(r)) => match calc.op {
+                            Arithmetic::Add => to_value(l + r),
+                            Arithmetic::Sub => to_value(l - r),
+                            Arithmetic::Mul => to_value(l * r),
+                            // dividing by zero gives null rather than infinity
+                            Arithmetic::Div => to_value(l / r),
+                        },
+                        _ => Value::Null,
+                    };
+                    row.insert(calc.name.clone(), value);
+                }
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// Runs `transforms` over `rows` in order. Errors give the row's index in
+/// `rows`; rows made by `aggregate` count as the first row of their group.
+pub fn apply(rows: Vec<Row>, transforms: &[Transform]) -> Result<Vec<Row>, TransformError> {
+    let rows = transforms
+        .iter()
+        .try_fold(rows.into_iter().enumerate().collect(), |rows, t| t.apply_numbered(rows))?;
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
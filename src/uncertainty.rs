@@ -0,0 +1,82 @@
+//! Estimates with lower and upper bounds from repeated samples, for error
+//! bars and bands.
+use crate::bar::BarSeries;
+use crate::series::Series;
+use crate::stats::{quantile, sorted, std_dev};
+
+/// How far the bounds reach from the estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spread {
+    /// The mean plus and minus this many standard deviations
+    StdDev(f64),
+    /// The median between these two percentiles (0 to 100)
+    Percentiles(f64, f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub center: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Estimate {
+    /// The estimate for the finite `samples`, or `None` if there are none.
+    pub fn new(samples: &[f64], spread: Spread) -> Option<Self> {
+        let sorted = sorted(samples);
+        if sorted.is_empty() {
+            return None;
+        }
+        Some(match spread {
+            Spread::StdDev(k) => {
+                let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+                let reach = std_dev(&sorted) * k.abs();
+                Self {
+                    center: mean,
+                    lower: mean - reach,
+                    upper: mean + reach,
+                }
+            }
+            Spread::Percentiles(lo, hi) => {
+                let at = |p: f64| quantile(&sorted, p.clamp(0.0, 100.0) / 100.0);
+                let (lower, upper) = (at(lo.min(hi))?, at(lo.max(hi))?);
+                Self {
+                    center: at(50.0)?,
+                    lower,
+                    upper,
+                }
+            }
+        })
+    }
+}
+
+/// A series through the estimate of each x's samples, with a band between
+/// the bounds. Xs without samples are left out.
+///
+/// Series hold whole numbers, so the estimate and bounds are multiplied by
+/// `scale` and rounded. Pick a `scale` that keeps them apart, e.g. 1000 for
+/// latencies in seconds; with 1 a band narrower than one unit disappears.
+pub fn band_series<S: Into<String>>(name: S, groups: &[(isize, Vec<f64>)], spread: Spread, scale: f64) -> Series {
+    let (data, bounds) = groups
+        .iter()
+        .filter_map(|(x, samples)| {
+            let e = Estimate::new(samples, spread)?;
+            let round = |v: f64| (v * scale).round() as isize;
+            Some(((*x, round(e.center)), (round(e.lower), round(e.upper))))
+        })
+        .unzip();
+    Series::new(name, data).with_bounds(bounds)
+}
+
+/// One bar per category at the estimate of its samples, with error bars.
+/// Categories without samples get no bar.
+pub fn error_bar_series<S: Into<String>>(name: S, groups: &[Vec<f64>], spread: Spread) -> BarSeries {
+    let (values, bounds) = groups
+        .iter()
+        .map(|samples| match Estimate::new(samples, spread) {
+            Some(e) => (e.center as f32, (e.lower as f32, e.upper as f32)),
+            None => (f32::NAN, (f32::NAN, f32::NAN)),
+        })
+        .unzip();
+    BarSeries::new(name, values).with_bounds(bounds)
+}
@@ -311,6 +311,8 @@ impl<'a> DrawingBackend for VTagWrapper<'a> {
             <image
                 x=pos.0
                 y=pos.1
+                width=iw
+                height=ih
                 href=data
             />
         });
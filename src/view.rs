@@ -0,0 +1,151 @@
+use std::ops::Range;
+use yew::prelude::*;
+
+/// The visible extents of a chart, in data units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Domain {
+    pub x: (f32, f32),
+    pub y: (f32, f32),
+}
+
+impl Domain {
+    pub fn new(x: (f32, f32), y: (f32, f32)) -> Self {
+        Self { x, y }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x.1 - self.x.0
+    }
+
+    pub fn height(&self) -> f32 {
+        self.y.1 - self.y.0
+    }
+
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.x.0 && x <= self.x.1 && y >= self.y.0 && y <= self.y.1
+    }
+
+    /// The indices of `points`, sorted by x, that fall within the x domain,
+    /// plus the neighbour past each edge so lines leaving the plot reach it.
+    pub fn visible<T>(&self, points: &[T], x: impl Fn(&T) -> f32) -> Range<usize> {
+        let start = points.iter().position(|p| x(p) >= self.x.0).unwrap_or(points.len());
+        let end = points.iter().rposition(|p| x(p) <= self.x.1).map(|i| i + 1).unwrap_or(0);
+        let end = (end + 1).min(points.len());
+        start.saturating_sub(1).min(end)..end
+    }
+
+    /// Scales each axis around `anchor` by `factor` (below 1 zooms in).
+    ///
+    /// The resulting span of each axis is kept between `base / max_zoom` and
+    /// `base / min_zoom`, where `base` is the span of the same axis in `base`.
+    pub fn zoom_at(
+        &self,
+        base: &Domain,
+        anchor: (f32, f32),
+        factor: (f32, f32),
+        (min_zoom, max_zoom): (f32, f32),
+    ) -> Self {
+        let scale_axis = |(lo, hi): (f32, f32), base_span: f32, anchor: f32, factor: f32| {
+            let span = hi - lo;
+            if span == 0.0 {
+                return (lo, hi);
+            }
+            let target = (span * factor).max(base_span / max_zoom).min(base_span / min_zoom);
+            let f = target / span;
+            (anchor - (anchor - lo) * f, anchor + (hi - anchor) * f)
+        };
+        Self {
+            x: scale_axis(self.x, base.width(), anchor.0, factor.0),
+            y: scale_axis(self.y, base.height(), anchor.1, factor.1),
+        }
+    }
+
+    /// Shifts the domain by a delta in data units.
+    pub fn pan(&self, (dx, dy): (f32, f32)) -> Self {
+        Self {
+            x: (self.x.0 + dx, self.x.1 + dx),
+            y: (self.y.0 + dy, self.y.1 + dy),
+        }
+    }
+}
+
+/// Maps between SVG user units inside a plot area and data space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub domain: Domain,
+}
+
+impl Projection {
+    /// Builds a projection from a plotters pixel range, e.g. the result of
+    /// `chart.plotting_area().get_pixel_range()`.
+    pub fn from_pixel_range((x, y): (Range<i32>, Range<i32>), domain: Domain) -> Self {
+        Self {
+            left: x.start as f32,
+            top: y.start as f32,
+            width: (x.end - x.start) as f32,
+            height: (y.end - y.start) as f32,
+            domain,
+        }
+    }
+
+    pub fn contains(&self, (px, py): (f32, f32)) -> bool {
+        px >= self.left && px <= self.left + self.width && py >= self.top && py <= self.top + self.height
+    }
+
+    pub fn to_data(&self, (px, py): (f32, f32)) -> (f32, f32) {
+        let d = &self.domain;
+        (
+            d.x.0 + (px - self.left) / self.width * d.width(),
+            d.y.1 - (py - self.top) / self.height * d.height(),
+        )
+    }
+
+    pub fn to_pixel(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let d = &self.domain;
+        (
+            self.left + (x - d.x.0) / d.width() * self.width,
+            self.top + (d.y.1 - y) / d.height() * self.height,
+        )
+    }
+
+    /// Converts a movement in SVG units into the matching movement in data units.
+    pub fn delta_to_data(&self, (dx, dy): (f32, f32)) -> (f32, f32) {
+        (
+            dx / self.width * self.domain.width(),
+            -dy / self.height * self.domain.height(),
+        )
+    }
+}
+
+/// Converts a client (page) position into the user units of the svg behind
+/// `node`, given its `viewBox`. The svg must use `preserveAspectRatio="none"`.
+pub fn client_to_svg(
+    node: &NodeRef,
+    (min_x, min_y, width, height): (f32, f32, f32, f32),
+    (client_x, client_y): (i32, i32),
+) -> Option<(f32, f32)> {
+    let el = node.cast::<web_sys::Element>()?;
+    let rect = el.get_bounding_client_rect();
+    let rect = (rect.left(), rect.top(), rect.width(), rect.height());
+    client_in_rect(rect, (min_x, min_y, width, height), (client_x, client_y))
+}
+
+/// Converts a client position into view box units, given the client rect
+/// (left, top, width, height) the view box is stretched over.
+pub fn client_in_rect(
+    (left, top, rect_width, rect_height): (f64, f64, f64, f64),
+    (min_x, min_y, width, height): (f32, f32, f32, f32),
+    (client_x, client_y): (i32, i32),
+) -> Option<(f32, f32)> {
+    if rect_width == 0.0 || rect_height == 0.0 {
+        return None;
+    }
+    Some((
+        min_x + ((client_x as f64 - left) / rect_width) as f32 * width,
+        min_y + ((client_y as f64 - top) / rect_height) as f32 * height,
+    ))
+}
@@ -0,0 +1,53 @@
+use yew_charts::bar::{value_extent, Bands, Frame, Orientation};
+
+fn near(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+#[test]
+fn bands_split_the_axis_with_equal_padding() {
+    let bands = Bands::new(2, 250.0, 0.5);
+    assert!(near(bands.bandwidth, 50.0));
+    assert!(near(bands.offset(0), 50.0));
+    assert!(near(bands.offset(1), 150.0));
+    // no padding: the bands tile the axis
+    let tiled = Bands::new(4, 400.0, 0.0);
+    assert!(near(tiled.bandwidth, 100.0));
+    assert!(near(tiled.offset(3), 300.0));
+    assert!(Bands::new(0, 400.0, 0.2).bandwidth.is_finite());
+}
+
+#[test]
+fn frame_maps_values_inside_the_margins() {
+    // margins are 90 top, 40 right, 50 bottom, 60 left
+    let vertical = Frame::new(400, 300, Orientation::Vertical, (0.0, 10.0));
+    assert_eq!((vertical.width, vertical.height, vertical.band_length()), (300.0, 160.0, 300.0));
+    assert_eq!(vertical.value_pos(0.0), 250.0);
+    assert_eq!(vertical.value_pos(5.0), 170.0);
+    // out of range values stick to the edge
+    assert_eq!(vertical.value_pos(20.0), 90.0);
+    assert_eq!(vertical.point(10.0, 5.0), (70.0, 170.0));
+    assert_eq!(vertical.rect(10.0, 20.0, 0.0, 5.0), (70.0, 170.0, 20.0, 80.0));
+
+    let horizontal = Frame::new(400, 300, Orientation::Horizontal, (0.0, 10.0));
+    assert_eq!(horizontal.band_length(), 160.0);
+    assert_eq!(horizontal.value_pos(5.0), 210.0);
+    assert_eq!(horizontal.point(10.0, 5.0), (210.0, 100.0));
+    assert_eq!(horizontal.rect(10.0, 20.0, 5.0, 0.0), (60.0, 100.0, 150.0, 20.0));
+}
+
+#[test]
+fn degenerate_domains_are_widened() {
+    let frame = Frame::new(400, 300, Orientation::Vertical, (5.0, 5.0));
+    assert_eq!(frame.domain, (4.0, 6.0));
+    assert_eq!(frame.value_pos(5.0), 170.0);
+}
+
+#[test]
+fn value_extent_includes_zero() {
+    assert_eq!(value_extent(vec![3.0, -2.0, f32::NAN]), (-2.0, 3.0));
+    assert_eq!(value_extent(vec![5.0, 5.0]), (0.0, 5.0));
+    assert_eq!(value_extent(vec![-4.0]), (-4.0, 0.0));
+    assert_eq!(value_extent(vec![0.0]), (0.0, 1.0));
+    assert_eq!(value_extent(Vec::new()), (0.0, 1.0));
+}
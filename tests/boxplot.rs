@@ -0,0 +1,37 @@
+use yew_charts::boxplot::{violin, Summary};
+
+#[test]
+fn quartiles_whiskers_and_outliers() {
+    let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 40.0, -20.0, f64::NAN];
+    let s = Summary::new(&samples, 1.5).unwrap();
+    assert_eq!(s.count, 11);
+    assert_eq!((s.min, s.max), (-20.0, 40.0));
+    assert_eq!((s.q1, s.median, s.q3), (2.5, 5.0, 7.5));
+    // the whiskers stop at the last samples within 7.5 of the box
+    assert_eq!((s.low_whisker, s.high_whisker), (1.0, 9.0));
+    assert_eq!(s.outliers, vec![-20.0, 40.0]);
+
+    let wide = Summary::new(&samples, 10.0).unwrap();
+    assert_eq!((wide.low_whisker, wide.high_whisker), (-20.0, 40.0));
+    assert!(wide.outliers.is_empty());
+}
+
+#[test]
+fn single_and_missing_samples() {
+    let s = Summary::new(&[3.0], 1.5).unwrap();
+    assert_eq!((s.q1, s.median, s.q3, s.low_whisker, s.high_whisker), (3.0, 3.0, 3.0, 3.0, 3.0));
+    assert!(Summary::new(&[f64::NAN], 1.5).is_none());
+    assert!(violin(&[], 1.0).is_empty());
+}
+
+#[test]
+fn violins_span_the_samples() {
+    let curve = violin(&[0.0, 1.0, 1.0, 2.0], 0.5);
+    assert_eq!(curve.first().unwrap().0, 0.0);
+    assert_eq!(curve.last().unwrap().0, 2.0);
+    // symmetric samples give a symmetric density peaking in the middle
+    let (first, last) = (curve.first().unwrap().1, curve.last().unwrap().1);
+    assert!((first - last).abs() < 1e-12);
+    let peak = curve.iter().cloned().fold((0.0, 0.0), |a, b| if b.1 > a.1 { b } else { a });
+    assert!((peak.0 - 1.0f64).abs() < 0.02);
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yew-charts")).args(args).output().unwrap()
+}
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("yew-charts-cli-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stderr(out: &Output) -> String {
+    String::from_utf8_lossy(&out.stderr).into_owned()
+}
+
+#[test]
+fn bad_arguments_exit_with_usage() {
+    for args in &[
+        &[][..],
+        &["draw", "spec.json"],
+        &["render"],
+        &["render", "spec.json", "--bogus"],
+        &["render", "spec.json", "extra.json"],
+        &["render", "spec.json", "-o"],
+        &["render", "spec.json", "--format", "gif"],
+    ] {
+        let out = run(args);
+        assert_eq!(out.status.code(), Some(2), "{:?}: {}", args, stderr(&out));
+        assert!(stderr(&out).contains("usage:"), "{:?}", args);
+    }
+    let help = run(&["--help"]);
+    assert_eq!(help.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&help.stdout).contains("usage:"));
+}
+
+#[test]
+fn format_must_match_the_output_file() {
+    let dir = scratch("format");
+    let out_file = dir.join("out.svg");
+    let out = run(&["render", "spec.json", "-o", out_file.to_str().unwrap(), "--format", "png"]);
+    assert_eq!(out.status.code(), Some(2), "{}", stderr(&out));
+    assert!(stderr(&out).contains("doesn't match"), "{}", stderr(&out));
+    assert!(!out_file.exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn bad_specs_exit_with_an_error() {
+    let dir = scratch("spec");
+    let spec = dir.join("broken.json");
+    fs::write(&spec, "{\"kind\": ").unwrap();
+    let out = run(&["render", spec.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr(&out).contains("broken.json"), "{}", stderr(&out));
+    let missing = run(&["render", dir.join("missing.json").to_str().unwrap()]);
+    assert_eq!(missing.status.code(), Some(1));
+    // a directory without specs is a usage mistake
+    let empty = scratch("empty");
+    assert_eq!(run(&["render", empty.to_str().unwrap()]).status.code(), Some(2));
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(empty).unwrap();
+}
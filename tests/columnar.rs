@@ -0,0 +1,95 @@
+use yew_charts::columnar::{Column, ColumnarError, Columns};
+use yew_charts::table::Selection;
+
+fn sample() -> Columns {
+    let mut columns = Columns::new();
+    columns.add("t", Column::I64(vec![1, 2, 3])).unwrap();
+    columns.add("value", Column::F64(vec![0.5, f64::NAN, -2.25])).unwrap();
+    columns.add("host", Column::F32(vec![1.0, 2.0, 1.0])).unwrap();
+    columns
+}
+
+fn header(rows: u32, columns: u32) -> Vec<u8> {
+    let mut bytes = b"YCOL\x01\0\0\0".to_vec();
+    bytes.extend_from_slice(&rows.to_le_bytes());
+    bytes.extend_from_slice(&columns.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn round_trip() {
+    let columns = sample();
+    let bytes = columns.encode().unwrap();
+    // every body starts 8-byte aligned
+    assert_eq!(bytes.len() % 8, 0);
+    let decoded = Columns::decode(&bytes).unwrap();
+    assert_eq!(decoded.names(), columns.names());
+    assert_eq!(decoded.column("t").unwrap(), &Column::I64(vec![1, 2, 3]));
+    assert_eq!(decoded.column("host").unwrap(), &Column::F32(vec![1.0, 2.0, 1.0]));
+    assert!(decoded.column("value").unwrap().get(1).unwrap().is_nan());
+    assert_eq!(decoded.points("t", "value").unwrap(), vec![(1, 1), (3, -2)]);
+    let series = decoded.series(&Selection::new("t", "value")).unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(Columns::decode(&Columns::new().encode().unwrap()).unwrap(), Columns::new());
+}
+
+#[test]
+fn oversized_names_are_refused() {
+    let mut columns = Columns::new();
+    columns.add("x".repeat(70_000), Column::F32(vec![1.0])).unwrap();
+    assert!(matches!(
+        columns.encode(),
+        Err(ColumnarError::TooLarge { value: 70_000, .. })
+    ));
+}
+
+#[test]
+fn every_truncation_is_an_error() {
+    let bytes = sample().encode().unwrap();
+    for len in 0..bytes.len() {
+        assert!(Columns::decode(&bytes[..len]).is_err(), "decoded {} of {} bytes", len, bytes.len());
+    }
+}
+
+#[test]
+fn malformed_headers() {
+    assert!(matches!(Columns::decode(b"NOPE\x01\0\0\0\0\0\0\0\0\0\0\0"), Err(ColumnarError::Magic)));
+    let mut bytes = header(0, 0);
+    bytes[4] = 9;
+    assert!(matches!(Columns::decode(&bytes), Err(ColumnarError::Version(9))));
+    // a kind that doesn't exist
+    let mut bytes = header(1, 1);
+    bytes.extend_from_slice(&[7, 0, 1, 0, b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(matches!(Columns::decode(&bytes), Err(ColumnarError::Kind { kind: 7, .. })));
+    // a name that isn't utf-8
+    let mut bytes = header(0, 1);
+    bytes.extend_from_slice(&[1, 0, 1, 0, 0xFF, 0, 0, 0]);
+    assert!(matches!(Columns::decode(&bytes), Err(ColumnarError::Name)));
+    // far more columns claimed than the buffer holds
+    assert!(matches!(Columns::decode(&header(1, u32::MAX)), Err(ColumnarError::Truncated { .. })));
+}
+
+#[test]
+fn huge_row_counts_dont_wrap() {
+    // enough f64 rows that the body size overflows 32 bit offsets
+    let mut bytes = header(u32::MAX, 2);
+    bytes.extend_from_slice(&[2, 0, 1, 0, b'a', 2, 0, 1, 0, b'b', 0, 0, 0, 0, 0, 0]);
+    assert!(matches!(Columns::decode(&bytes), Err(ColumnarError::Truncated { .. })));
+}
+
+#[test]
+fn series_are_split_in_order_and_sorted_by_x() {
+    let mut columns = Columns::new();
+    columns.add("t", Column::I64(vec![3, 1, 2, 5, 4])).unwrap();
+    columns.add("v", Column::F32(vec![30.0, 10.0, 20.0, 50.0, 40.0])).unwrap();
+    columns.add("host", Column::F64(vec![2.0, 1.0, 2.0, 1.0, 2.0])).unwrap();
+    let series = columns.series(&Selection::new("t", "v").split_by("host")).unwrap();
+    let named: Vec<(&str, &[(isize, isize)])> = series.iter().map(|s| (s.name.as_str(), &s.data[..])).collect();
+    assert_eq!(
+        named,
+        vec![("2", &[(2, 20), (3, 30), (4, 40)][..]), ("1", &[(1, 10), (5, 50)][..])]
+    );
+    let single = columns.series(&Selection::new("t", "v")).unwrap();
+    assert_eq!(single[0].name, "v");
+    assert_eq!(single[0].data.iter().map(|(x, _)| *x).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+}
@@ -0,0 +1,51 @@
+use yew_charts::curve::{area_path, line_path, value_at, Curve};
+
+const POINTS: [(f32, f32); 3] = [(0.0, 10.0), (10.0, 20.0), (20.0, 0.0)];
+
+#[test]
+fn steps() {
+    assert_eq!(line_path(&POINTS[..2], Curve::Linear), "M0.0 10.0L10.0 20.0");
+    assert_eq!(line_path(&POINTS[..2], Curve::StepBefore), "M0.0 10.0L0.0 20.0L10.0 20.0");
+    assert_eq!(line_path(&POINTS[..2], Curve::StepAfter), "M0.0 10.0L10.0 10.0L10.0 20.0");
+    assert_eq!(
+        line_path(&POINTS[..2], Curve::StepMiddle),
+        "M0.0 10.0L5.0 10.0L5.0 20.0L10.0 20.0"
+    );
+    assert_eq!(line_path(&[], Curve::StepAfter), "");
+}
+
+#[test]
+fn monotone_curves_stay_between_their_points() {
+    let points = [(0.0, 0.0), (1.0, 0.0), (2.0, 10.0), (3.0, 10.0), (4.0, 12.0)];
+    let d = line_path(&points, Curve::Monotone);
+    assert_eq!(d.matches('C').count(), 4);
+    // every control point lies within the y range of its segment's ends
+    let segments = d.split(&['C', 'M'][..]).skip(2);
+    for (seg, w) in segments.zip(points.windows(2)) {
+        let numbers: Vec<f32> = seg.split(' ').map(|n| n.parse().unwrap()).collect();
+        let (lo, hi) = (w[0].1.min(w[1].1), w[0].1.max(w[1].1));
+        for y in &[numbers[1], numbers[3]] {
+            assert!(*y >= lo && *y <= hi, "{}", d);
+        }
+    }
+    // flat stretches stay flat
+    assert!(d.contains("C0.3 0.0 0.7 0.0 1.0 0.0"), "{}", d);
+}
+
+#[test]
+fn areas_close_back_along_the_base() {
+    let base = [(0.0, 0.0), (10.0, 0.0)];
+    assert_eq!(
+        area_path(&POINTS[..2], &base, Curve::StepAfter),
+        "M0.0 10.0L10.0 10.0L10.0 20.0L10.0 0.0L10.0 0.0L0.0 0.0Z"
+    );
+    assert_eq!(area_path(&[], &base, Curve::Linear), "");
+}
+
+#[test]
+fn baselines_are_interpolated_and_held() {
+    assert_eq!(value_at(&POINTS, 5.0), Some(15.0));
+    assert_eq!(value_at(&POINTS, -3.0), Some(10.0));
+    assert_eq!(value_at(&POINTS, 30.0), Some(0.0));
+    assert_eq!(value_at(&[], 1.0), None);
+}
@@ -0,0 +1,39 @@
+use yew::services::fetch::Response;
+use yew_charts::columnar::{self, Column, Columns};
+use yew_charts::fetch::{binary_series, get, text_series, MapFn};
+use yew_charts::table::{Options, Selection};
+
+fn response<T>(status: u16, body: T) -> Response<T> {
+    Response::builder().status(status).body(body).unwrap()
+}
+
+#[test]
+fn text_responses_go_through_map() {
+    let map: MapFn = Selection::new("t", "v").map_fn(Options::csv());
+    let series = text_series(response(200, Ok("t,v\n1,2\n3,4\n".to_string())), &map).unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].data, vec![(1, 2), (3, 4)]);
+    // map errors and failed requests both become the chart's error
+    assert!(text_series(response(200, Ok("t\n1\n".to_string())), &map).is_err());
+    let err = text_series(response(503, Ok("t,v\n1,2\n".to_string())), &map).unwrap_err();
+    assert!(err.contains("503"), "{}", err);
+}
+
+#[test]
+fn binary_responses_go_through_decode() {
+    let mut columns = Columns::new();
+    columns.add("t", Column::I64(vec![1, 2])).unwrap();
+    columns.add("v", Column::F32(vec![10.0, 20.0])).unwrap();
+    let decode = columnar::map_fn(Selection::new("t", "v"));
+    let series = binary_series(response(200, Ok(columns.encode().unwrap())), &decode).unwrap();
+    assert_eq!(series[0].data, vec![(1, 10), (2, 20)]);
+    assert!(binary_series(response(200, Ok(b"junk".to_vec())), &decode).is_err());
+    assert!(binary_series(response(404, Ok(Vec::new())), &decode).is_err());
+}
+
+#[test]
+fn bad_urls_are_errors() {
+    assert_eq!(get("/api/series?from=0").unwrap().uri(), "/api/series?from=0");
+    let err = get("not a url").unwrap_err();
+    assert!(err.contains("not a url"), "{}", err);
+}
@@ -0,0 +1,57 @@
+use yew_charts::gesture::{Gesture, TouchTracker};
+
+#[test]
+fn pinches_zoom_around_the_midpoint() {
+    let mut touches = TouchTracker::default();
+    touches.reset(vec![(100.0, 100.0), (200.0, 100.0)]);
+    // fingers spread to twice the distance, so the view halves
+    let gesture = touches.update(vec![(50.0, 100.0), (250.0, 100.0)]);
+    assert_eq!(
+        gesture,
+        Some(Gesture::Pinch {
+            anchor: (150.0, 100.0),
+            factor: 0.5,
+            shift: (0.0, 0.0)
+        })
+    );
+    // pinching in while both fingers drift down zooms out and pans
+    let gesture = touches.update(vec![(100.0, 120.0), (200.0, 120.0)]);
+    assert_eq!(
+        gesture,
+        Some(Gesture::Pinch {
+            anchor: (150.0, 100.0),
+            factor: 2.0,
+            shift: (0.0, 20.0)
+        })
+    );
+    // fingers on top of each other give no factor
+    assert_eq!(touches.update(vec![(150.0, 120.0), (150.0, 120.0)]), None);
+    assert_eq!(touches.press(), None);
+}
+
+#[test]
+fn presses_end_when_the_finger_moves() {
+    let mut touches = TouchTracker::default();
+    touches.reset(vec![(10.0, 10.0)]);
+    assert_eq!(touches.press(), Some((10.0, 10.0)));
+    // small drift keeps the press and doesn't pan
+    assert_eq!(touches.update(vec![(14.0, 14.0)]), None);
+    assert_eq!(touches.press(), Some((10.0, 10.0)));
+    // leaving the slop cancels the press and pans from the last position
+    assert_eq!(touches.update(vec![(30.0, 10.0)]), Some(Gesture::Pan((20.0, 0.0))));
+    assert_eq!(touches.press(), None);
+    // coming back doesn't bring the press back
+    assert_eq!(touches.update(vec![(10.0, 10.0)]), Some(Gesture::Pan((-20.0, 0.0))));
+    assert_eq!(touches.press(), None);
+}
+
+#[test]
+fn second_fingers_are_never_presses() {
+    let mut touches = TouchTracker::default();
+    touches.reset(vec![(10.0, 10.0)]);
+    touches.reset(vec![(10.0, 10.0), (50.0, 50.0)]);
+    assert_eq!(touches.press(), None);
+    // lifting one finger of a pinch doesn't start a press either
+    touches.reset(vec![(10.0, 10.0)]);
+    assert_eq!(touches.press(), None);
+}
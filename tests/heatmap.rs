@@ -0,0 +1,43 @@
+use yew_charts::heatmap::{ColorScale, Grid};
+use yew_charts::series::ramp_rgb;
+
+#[test]
+fn triples_are_bucketed_and_summed() {
+    let grid = Grid::from_triples(&[
+        (60.0, 0.1, 1.0),
+        (0.0, 0.5, 2.0),
+        (60.0, 0.1, 3.0),
+        (0.0, 0.1, 5.0),
+        (f64::NAN, 0.1, 7.0),
+    ]);
+    assert_eq!(grid.columns, vec!["0", "60"]);
+    assert_eq!(grid.rows, vec!["0.1", "0.5"]);
+    assert_eq!(grid.get(1, 0), Some(4.0));
+    assert_eq!(grid.get(0, 1), Some(2.0));
+    assert_eq!(grid.get(1, 1), None);
+    assert_eq!(grid.extent(), Some((2.0, 5.0)));
+}
+
+#[test]
+fn empty_grid_has_no_extent() {
+    assert_eq!(Grid::from_triples(&[]).extent(), None);
+}
+
+#[test]
+fn sequential_scale_spans_the_domain() {
+    let scale = ColorScale::sequential();
+    assert_eq!(scale.position(5.0, (0.0, 10.0)), 0.5);
+    assert_eq!(scale.position(-1.0, (0.0, 10.0)), 0.0);
+    assert_eq!(scale.fit((2.0, 5.0)), (2.0, 5.0));
+}
+
+#[test]
+fn diverging_scale_centers_the_middle_stop() {
+    let scale = ColorScale::diverging(0.0);
+    // each half is stretched on its own
+    assert_eq!(scale.position(-1.0, (-1.0, 4.0)), 0.0);
+    assert_eq!(scale.position(0.0, (-1.0, 4.0)), 0.5);
+    assert_eq!(scale.position(2.0, (-1.0, 4.0)), 0.75);
+    assert_eq!(scale.fit((-1.0, 4.0)), (-4.0, 4.0));
+    assert_eq!(ramp_rgb(scale.stops(), 0.5), [0xF7, 0xF7, 0xF7]);
+}
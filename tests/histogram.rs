@@ -0,0 +1,110 @@
+use yew_charts::histogram::{bins, density, heights, kde, log_height, silverman, Bin, Binning, LOG_FLOOR};
+use yew_charts::stats::{iqr, quantile, sorted, std_dev};
+
+fn counts(bins: &[Bin]) -> Vec<usize> {
+    bins.iter().map(|b| b.count).collect()
+}
+
+#[test]
+fn sturges_splits_range_evenly() {
+    let samples: Vec<f64> = (0..8).map(f64::from).collect();
+    let out = bins(&samples, Binning::Sturges);
+    // log2(8) + 1 bins, the top sample going in the last one
+    assert_eq!(counts(&out), vec![2, 2, 2, 2]);
+    assert_eq!(out[0].start, 0.0);
+    assert_eq!(out[3].end, 7.0);
+}
+
+#[test]
+fn fixed_width_aligns_to_multiples() {
+    let out = bins(&[3.0, 12.0, 14.0, 27.0], Binning::Width(10.0));
+    assert_eq!(
+        out.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(),
+        vec![(0.0, 10.0), (10.0, 20.0), (20.0, 30.0)]
+    );
+    assert_eq!(counts(&out), vec![1, 2, 1]);
+}
+
+#[test]
+fn fixed_count_and_freedman_diaconis() {
+    let samples = [1.0, 2.0, 3.0, 4.0, 100.0];
+    assert_eq!(counts(&bins(&samples, Binning::Count(2))), vec![4, 1]);
+    // the outlier doesn't widen the bins
+    let fd = bins(&samples, Binning::FreedmanDiaconis);
+    assert!(fd.len() > 20);
+    assert_eq!(fd.iter().map(|b| b.count).sum::<usize>(), 5);
+}
+
+#[test]
+fn degenerate_samples() {
+    assert!(bins(&[], Binning::Scott).is_empty());
+    assert!(bins(&[f64::NAN], Binning::Scott).is_empty());
+    assert_eq!(
+        bins(&[2.0, 2.0, f64::INFINITY], Binning::Scott),
+        vec![Bin {
+            start: 1.5,
+            end: 2.5,
+            count: 2
+        }]
+    );
+    // a useless width falls back to Sturges
+    assert_eq!(counts(&bins(&[0.0, 1.0], Binning::Width(-1.0))), vec![1, 1]);
+}
+
+#[test]
+fn kde_integrates_to_one() {
+    let samples = [1.0, 2.0, 2.5, 4.0];
+    let bw = silverman(&samples);
+    let xs: Vec<f64> = (0..=1000).map(|i| -10.0 + i as f64 * 0.025).collect();
+    let area: f64 = kde(&samples, bw, &xs).iter().sum::<f64>() * 0.025;
+    assert!((area - 1.0).abs() < 1e-3, "{}", area);
+}
+
+#[test]
+fn cumulative_heights_run_up_to_the_sample_count() {
+    let out = bins(&[1.0, 2.0, 2.0, 3.0, 9.0], Binning::Count(4));
+    assert_eq!(heights(&out, false), vec![3.0, 1.0, 0.0, 1.0]);
+    assert_eq!(heights(&out, true), vec![3.0, 4.0, 4.0, 5.0]);
+}
+
+#[test]
+fn single_samples_stand_above_the_log_floor() {
+    assert_eq!(log_height(100.0), 2.0);
+    assert_eq!(log_height(1.0), 0.0);
+    assert!(log_height(1.0) > LOG_FLOOR);
+    assert_eq!(log_height(0.0), LOG_FLOOR);
+    assert_eq!(log_height(0.1), LOG_FLOOR);
+}
+
+#[test]
+fn density_is_scaled_to_the_bars() {
+    let samples: Vec<f64> = (0..200).map(|i| (i % 20) as f64).collect();
+    let out = bins(&samples, Binning::Width(5.0));
+    let width = out[0].end - out[0].start;
+    let bw = silverman(&samples);
+    // counts per bin integrate to the samples times the bin width
+    let curve = density(&samples, &out, bw, false);
+    let step = curve[1].0 - curve[0].0;
+    let area: f64 = curve.iter().map(|(_, y)| y * step).sum::<f64>() / width;
+    assert!((area - 200.0).abs() < 20.0, "{}", area);
+    // the running integral ends near the sample count, less the tails past the bins
+    let cumulative = density(&samples, &out, bw, true);
+    let (_, last) = cumulative[cumulative.len() - 1];
+    assert!(last > 170.0 && last <= 200.0, "{}", last);
+    assert!(cumulative.windows(2).all(|w| w[0].1 <= w[1].1));
+    assert!(density(&samples, &[], bw, false).is_empty());
+}
+
+#[test]
+fn shared_stats() {
+    let s = sorted(&[3.0, f64::NAN, 1.0, 2.0, 4.0]);
+    assert_eq!(s, vec![1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(quantile(&s, 0.0), Some(1.0));
+    assert_eq!(quantile(&s, 0.5), Some(2.5));
+    assert_eq!(quantile(&s, 1.0), Some(4.0));
+    assert_eq!(iqr(&s), Some(1.5));
+    assert_eq!(quantile(&[], 0.5), None);
+    assert_eq!(iqr(&[]), None);
+    assert_eq!(quantile(&[7.0], 0.9), Some(7.0));
+    assert!((std_dev(&s) - 1.2909944).abs() < 1e-6);
+}
@@ -0,0 +1,39 @@
+use yew_charts::ohlc::{gaps, interval, positions, Candle, Gaps};
+use yew_charts::table::{format_timestamp, parse_timestamp};
+
+const DAY: f64 = 86_400.0;
+
+// thursday 2020-09-03 to the following tuesday, skipping the weekend
+fn week() -> Vec<Candle> {
+    let start = parse_timestamp("2020-09-03").unwrap();
+    [0.0, 1.0, 4.0, 5.0]
+        .iter()
+        .map(|d| Candle::new(start + d * DAY, 10.0, 12.0, 9.0, 11.0).with_volume(100.0))
+        .collect()
+}
+
+#[test]
+fn weekends_are_gaps() {
+    let candles = week();
+    assert_eq!(interval(&candles), DAY);
+    assert_eq!(gaps(&candles), vec![2]);
+    assert_eq!(positions(&candles, Gaps::Collapse), vec![0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(positions(&candles, Gaps::Show)[2], candles[2].time);
+}
+
+#[test]
+fn a_lone_candle_spans_a_day() {
+    let candles = &week()[..1];
+    assert_eq!(interval(candles), DAY);
+    assert!(gaps(candles).is_empty());
+    assert!(candles[0].is_up());
+}
+
+#[test]
+fn timestamps_round_trip() {
+    for s in &["1970-01-01", "2020-02-29", "1969-12-31", "2038-01-19"] {
+        assert_eq!(&format_timestamp(parse_timestamp(s).unwrap(), false), s);
+    }
+    let t = parse_timestamp("2020-09-07T13:45:30Z").unwrap();
+    assert_eq!(format_timestamp(t, true), "2020-09-07 13:45");
+}
@@ -0,0 +1,71 @@
+use std::f64::consts::PI;
+use yew_charts::pie::{layout, sector_path, spread_labels, Slice};
+
+fn slices() -> Vec<Slice> {
+    vec![
+        Slice::new("compute", 50.0),
+        Slice::new("storage", 30.0),
+        Slice::new("egress", 8.0),
+        Slice::new("dns", 2.0),
+        Slice::new("logs", 10.0),
+        Slice::new("refund", -5.0),
+    ]
+}
+
+#[test]
+fn sectors_cover_the_circle_in_order() {
+    let sectors = layout(&slices(), PI / 2.0, None, "Other");
+    let labels: Vec<&str> = sectors.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["compute", "storage", "egress", "dns", "logs"]);
+    assert_eq!(sectors[0].start, PI / 2.0);
+    assert_eq!(sectors[0].fraction, 0.5);
+    assert!((sectors[4].end - (PI / 2.0 + 2.0 * PI)).abs() < 1e-9);
+    assert_eq!(sectors[1].start, sectors[0].end);
+}
+
+#[test]
+fn small_slices_are_grouped_last() {
+    let sectors = layout(&slices(), 0.0, Some(0.09), "Other");
+    let labels: Vec<&str> = sectors.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["compute", "storage", "logs", "Other"]);
+    assert_eq!(sectors[3].slices, vec![2, 3]);
+    assert_eq!(sectors[3].value, 10.0);
+
+    // one small slice keeps its own name and place
+    let sectors = layout(&slices(), 0.0, Some(0.05), "Other");
+    assert_eq!(sectors.len(), 5);
+    assert_eq!(sectors[3].label, "dns");
+}
+
+#[test]
+fn nothing_to_show() {
+    assert!(layout(&[Slice::new("a", 0.0), Slice::new("b", f64::NAN)], 0.0, None, "Other").is_empty());
+}
+
+#[test]
+fn paths_for_wedges_rings_and_full_circles() {
+    assert_eq!(
+        sector_path((50.0, 50.0), 0.0, 10.0, 0.0, PI / 2.0),
+        "M50.00 40.00A10.00 10.00 0 0 1 60.00 50.00L50.00 50.00Z"
+    );
+    assert_eq!(
+        sector_path((0.0, 0.0), 5.0, 10.0, 0.0, 1.5 * PI),
+        "M0.00 -10.00A10.00 10.00 0 1 1 -10.00 0.00L-5.00 0.00A5.00 5.00 0 1 0 0.00 -5.00Z"
+    );
+    // a full circle is drawn as two halves
+    assert_eq!(sector_path((0.0, 0.0), 0.0, 1.0, 0.0, 2.0 * PI).matches('A').count(), 2);
+}
+
+#[test]
+fn labels_spread_within_the_chart() {
+    let mut ys = vec![10.0, 12.0, 50.0];
+    spread_labels(&mut ys, 14.0, 200.0);
+    assert_eq!(ys, vec![10.0, 24.0, 50.0]);
+    // crowded at the bottom, so they move up instead of off the chart
+    let mut ys = vec![150.0, 190.0, 195.0, 199.0];
+    spread_labels(&mut ys, 14.0, 200.0);
+    assert_eq!(ys, vec![150.0, 165.0, 179.0, 193.0]);
+    let mut ys = vec![0.0];
+    spread_labels(&mut ys, 14.0, 200.0);
+    assert_eq!(ys, vec![7.0]);
+}
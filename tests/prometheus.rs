@@ -0,0 +1,104 @@
+use yew_charts::prometheus::{
+    exposition_series, parse_exposition, parse_query_range, MetricType, NameTemplate,
+};
+
+const EXPOSITION: &str = r#"
+# HELP http_requests_total Requests served,\nby code.
+# TYPE http_requests_total counter
+http_requests_total{code="200",path="/a # b"} 1027 1600000000000
+http_requests_total{code="500", path="/\"quoted\""} 3 1600000000000
+# TYPE latency_seconds histogram
+latency_seconds_bucket{le="0.1"} 8
+latency_seconds_bucket{le="+Inf"} 10
+latency_seconds_sum 0.42
+latency_seconds_count 10
+temperature NaN
+"#;
+
+const OPEN_METRICS: &str = r#"# TYPE rpc_seconds histogram
+rpc_seconds_bucket{le="1",note="a # in a label"} 4 1600000000.5 # {trace_id="abc"} 0.7 1600000000.1
+rpc_seconds_count 4 # {trace_id="def"} 0.9
+# EOF
+"#;
+
+#[test]
+fn families_gather_their_samples() {
+    let families = parse_exposition(EXPOSITION).unwrap();
+    let names: Vec<&str> = families.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["http_requests_total", "latency_seconds", "temperature"]);
+    let requests = &families[0];
+    assert_eq!(requests.kind, MetricType::Counter);
+    assert_eq!(requests.help, "Requests served,\nby code.");
+    assert_eq!(requests.samples[0].labels[1], ("path".to_string(), "/a # b".to_string()));
+    assert_eq!(requests.samples[1].labels[1], ("path".to_string(), "/\"quoted\"".to_string()));
+    // prometheus timestamps are milliseconds
+    assert_eq!(requests.samples[0].timestamp, Some(1600000000.0));
+    assert_eq!(families[1].kind, MetricType::Histogram);
+    assert_eq!(families[1].samples.len(), 4);
+    assert_eq!(families[1].samples[1].labels[0].1, "+Inf");
+    assert!(families[2].samples[0].value.is_nan());
+}
+
+#[test]
+fn exemplars_are_dropped_after_the_value() {
+    let families = parse_exposition(OPEN_METRICS).unwrap();
+    let samples = &families[0].samples;
+    assert_eq!(samples[0].labels[1].1, "a # in a label");
+    assert_eq!((samples[0].value, samples[0].timestamp), (4.0, Some(1600000000.5)));
+    assert_eq!((samples[1].value, samples[1].timestamp), (4.0, None));
+}
+
+#[test]
+fn malformed_samples_report_their_line() {
+    let err = parse_exposition("ok 1\nbroken{code=\"200} 1\n").unwrap_err();
+    assert!(err.to_string().starts_with("line 2:"), "{}", err);
+    let err = parse_exposition("# TYPE x gauge\nx one\n").unwrap_err();
+    assert!(err.to_string().starts_with("line 2:"), "{}", err);
+}
+
+#[test]
+fn scaled_series_keep_small_values() {
+    let families = parse_exposition(EXPOSITION).unwrap();
+    let series = exposition_series(&families, &NameTemplate::new("{{__name__}} {{code}}"), 1000.0, 1700000000.0);
+    let latency = series.iter().find(|s| s.name == "latency_seconds_sum").unwrap();
+    assert_eq!(latency.data, vec![(1700000000, 420)]);
+    let ok = series.iter().find(|s| s.name == "http_requests_total 200").unwrap();
+    assert_eq!(ok.data, vec![(1600000000, 1027000)]);
+    // NaN samples are left out
+    assert!(series.iter().all(|s| s.name != "temperature"));
+}
+
+const QUERY_RANGE: &str = r#"{
+  "status": "success",
+  "data": {
+    "resultType": "matrix",
+    "result": [
+      {
+        "metric": {"__name__": "up", "job": "node"},
+        "values": [[1600000000, "0.25"], [1600000015, "NaN"], [1600000030, "+Inf"], [1600000045, "1"]]
+      },
+      {"metric": {"job": "db"}, "values": [[1600000000.4, "-0.5"]]}
+    ]
+  }
+}"#;
+
+#[test]
+fn query_range_skips_values_that_cant_be_drawn() {
+    let series = parse_query_range(QUERY_RANGE, &NameTemplate::default(), 100.0).unwrap();
+    assert_eq!(series[0].name, "up{job=\"node\"}");
+    assert_eq!(series[0].data, vec![(1600000000, 25), (1600000045, 100)]);
+    assert_eq!(series[1].name, "{job=\"db\"}");
+    assert_eq!(series[1].data, vec![(1600000000, -50)]);
+    // unscaled, a quarter rounds away
+    let series = parse_query_range(QUERY_RANGE, &NameTemplate::new("{{job}}"), 1.0).unwrap();
+    assert_eq!(series[0].data, vec![(1600000000, 0), (1600000045, 1)]);
+}
+
+#[test]
+fn query_errors() {
+    let failed = r#"{"status": "error", "errorType": "bad_data", "error": "parse error"}"#;
+    let err = parse_query_range(failed, &NameTemplate::default(), 1.0).unwrap_err();
+    assert_eq!(err.to_string(), "query failed (bad_data): parse error");
+    let scalar = r#"{"status": "success", "data": {"resultType": "scalar", "result": []}}"#;
+    assert!(parse_query_range(scalar, &NameTemplate::default(), 1.0).is_err());
+}
@@ -0,0 +1,50 @@
+use yew_charts::scatter::{ScatterPoint, SizeScale};
+use yew_charts::series::{dim, ramp};
+use yew_charts::table::{Options, Table};
+
+#[test]
+fn size_scale_grows_area_linearly() {
+    let scale = SizeScale {
+        domain: (0.0, 100.0),
+        range: (0.0, 10.0),
+    };
+    assert_eq!(scale.radius(0.0), 0.0);
+    assert_eq!(scale.radius(100.0), 10.0);
+    // a quarter of the value is a quarter of the area
+    assert_eq!(scale.radius(25.0), 5.0);
+    assert_eq!(scale.radius(500.0), 10.0);
+}
+
+#[test]
+fn ramp_interpolates_between_stops() {
+    let stops = ["#000000", "#FF0000", "#FFFFFF"];
+    assert_eq!(ramp(&stops, 0.0), "#000000");
+    assert_eq!(ramp(&stops, 0.25), "#800000");
+    assert_eq!(ramp(&stops, 0.5), "#FF0000");
+    assert_eq!(ramp(&stops, 2.0), "#FFFFFF");
+    assert_eq!(ramp(&["#123456"], 0.7), "#123456");
+}
+
+#[test]
+fn malformed_colors_are_left_alone() {
+    assert_eq!(dim("#000000"), "#BFBFBF");
+    assert_eq!(dim("#ééé"), "#ééé");
+    assert_eq!(dim("#1é2é"), "#1é2é");
+    assert_eq!(dim("red"), "red");
+    // unreadable stops fall back to the palette instead of panicking
+    assert_eq!(ramp(&["#ééé"], 0.5), ramp(&["#1F77B4"], 0.5));
+}
+
+#[test]
+fn table_columns_become_sizes_and_colors() {
+    let table = Table::parse("x,y,pop,gdp\n1,2,10,0.5\n3,,20,0.1\n5,6,,0.9\n", &Options::csv()).unwrap();
+    let points = table.scatter_points("x", "y", Some("pop"), Some("gdp")).unwrap();
+    assert_eq!(
+        points,
+        vec![
+            ScatterPoint::new(1.0, 2.0).with_size(10.0).with_color(0.5),
+            ScatterPoint::new(5.0, 6.0).with_color(0.9),
+        ]
+    );
+    assert!(table.scatter_points("x", "y", Some("nope"), None).is_err());
+}
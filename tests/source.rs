@@ -0,0 +1,103 @@
+use std::time::Duration;
+use yew_charts::source::{Backoff, Format, Precision, SourceError};
+
+fn line_of(err: SourceError) -> usize {
+    match err {
+        SourceError::Line { line, .. } => line,
+        e => panic!("unexpected error {}", e),
+    }
+}
+
+#[test]
+fn json_samples() {
+    let one = Format::Json.parse(r#"{"series": "cpu", "x": 1600000000, "y": 41.6}"#).unwrap();
+    assert_eq!(one, vec![("cpu".to_string(), (1600000000, 42))]);
+    let many = Format::Json.parse(r#"[{"x": 1, "y": 2}, {"series": "b", "x": 3.7, "y": -4.4}]"#).unwrap();
+    assert_eq!(many, vec![("".to_string(), (1, 2)), ("b".to_string(), (3, -4))]);
+    assert!(matches!(Format::Json.parse("{\"x\": 1}"), Err(SourceError::Json(_))));
+}
+
+#[test]
+fn json_x_and_y_must_fit() {
+    let err = Format::Json.parse(r#"[{"x": 1, "y": 2}, {"x": 1e300, "y": 2}]"#).unwrap_err();
+    assert!(matches!(err, SourceError::X { sample: 1, .. }), "{}", err);
+    let err = Format::Json.parse(r#"[{"x": 1, "y": 1e300}]"#).unwrap_err();
+    assert!(matches!(err, SourceError::Y { sample: 0, .. }), "{}", err);
+}
+
+#[test]
+fn line_protocol_fields_and_precision() {
+    let text = "cpu,host=a usage=42.4,value=7i 1600000000123456789\nmem free=-3i 1600000001000000000";
+    let seconds = Format::LineProtocol(Precision::Seconds).parse(text).unwrap();
+    assert_eq!(
+        seconds,
+        vec![
+            ("cpu.usage".to_string(), (1600000000, 42)),
+            ("cpu".to_string(), (1600000000, 7)),
+            ("mem.free".to_string(), (1600000001, -3)),
+        ]
+    );
+    if std::mem::size_of::<isize>() == 8 {
+        let millis = Format::LineProtocol(Precision::Milliseconds).parse(text).unwrap();
+        assert_eq!(millis[0].1, (1600000000123, 42));
+        let nanos = Format::LineProtocol(Precision::Nanoseconds).parse(text).unwrap();
+        assert_eq!(nanos[0].1, (1600000000123456789, 42));
+    }
+    // float timestamps and times before the epoch round down
+    let early = Format::LineProtocol(Precision::Seconds).parse("cpu value=1 -1.5e9").unwrap();
+    assert_eq!(early[0].1, (-2, 1));
+}
+
+#[test]
+fn comments_and_blank_lines_are_skipped() {
+    let text = "# header\n\n   \ncpu value=1 1000000000\n  # indented comment\n";
+    let samples = Format::LineProtocol(Precision::Seconds).parse(text).unwrap();
+    assert_eq!(samples, vec![("cpu".to_string(), (1, 1))]);
+}
+
+#[test]
+fn malformed_lines_report_their_number() {
+    let parse = |text: &str| Format::LineProtocol(Precision::Seconds).parse(text).unwrap_err();
+    assert_eq!(line_of(parse("cpu value=1 1\ncpu")), 2);
+    assert_eq!(line_of(parse("# c\ncpu value=1")), 2);
+    assert_eq!(line_of(parse("cpu value=1 1\n\ncpu value=x 1")), 3);
+    assert_eq!(line_of(parse("cpu value 1")), 1);
+    assert_eq!(line_of(parse("cpu value=1 soon")), 1);
+    let err = parse("cpu value=1 99999999999999999999999");
+    assert!(err.to_string().contains("timestamp"), "{}", err);
+}
+
+#[test]
+fn string_and_boolean_fields_are_skipped() {
+    let parse = |text: &str| Format::LineProtocol(Precision::Seconds).parse(text);
+    let text = r#"cpu,host=a msg="busy, very",up=true,usage=42u,load=1.5 1000000000"#;
+    assert_eq!(
+        parse(text).unwrap(),
+        vec![("cpu.usage".to_string(), (1, 42)), ("cpu.load".to_string(), (1, 2))]
+    );
+    assert_eq!(line_of(parse("cpu up=true,host=\"a\" 1").unwrap_err()), 1);
+    assert_eq!(line_of(parse("cpu value=inf 1").unwrap_err()), 1);
+}
+
+#[test]
+fn escaped_spaces_and_commas_in_names() {
+    let text = r#"disk\ io,path=/a\ b read\,bytes=3,msg="a b" 1000000000"#;
+    let samples = Format::LineProtocol(Precision::Seconds).parse(text).unwrap();
+    assert_eq!(samples, vec![("disk io.read,bytes".to_string(), (1, 3))]);
+}
+
+#[test]
+fn backoff_doubles_up_to_its_cap_and_resets() {
+    let mut backoff = Backoff::new(Duration::from_millis(500), Duration::from_secs(3));
+    let delays: Vec<u128> = (0..5).map(|_| backoff.fail().as_millis()).collect();
+    assert_eq!(delays, vec![500, 1000, 2000, 3000, 3000]);
+    assert_eq!(backoff.attempt(), 5);
+    backoff.reset();
+    assert_eq!(backoff.attempt(), 0);
+    assert_eq!(backoff.fail(), Duration::from_millis(500));
+    // many failures neither overflow nor pass the cap
+    let mut backoff = Backoff::new(Duration::from_secs(u64::MAX / 2), Duration::from_secs(30));
+    for _ in 0..100 {
+        assert!(backoff.fail() <= Duration::from_secs(30));
+    }
+}
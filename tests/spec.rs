@@ -0,0 +1,32 @@
+use yew_charts::spec::ChartSpec;
+
+fn spec(legend: &str) -> ChartSpec {
+    let json = format!(
+        r#"{{"legend": {}, "series": [
+            {{"name": "a", "points": [[0, 1], [1, 2]]}},
+            {{"name": "a rather long series name", "points": [[0, 3], [1, 4]]}}
+        ]}}"#,
+        legend
+    );
+    ChartSpec::from_json(&json).unwrap()
+}
+
+#[test]
+fn right_legends_widen_the_margin() {
+    let right = spec(r#"{"position": "right"}"#);
+    let series = right.resolve().unwrap();
+    let (_, margin, _, _) = right.margins(&series);
+    // the widest entry starts 8 units into the margin and must end inside the svg
+    assert!(8.0 + 32.0 + 7.0 * 25.0 <= margin as f32, "{}", margin);
+    assert_eq!(right.margins(&series[..1]).1, 47);
+}
+
+#[test]
+fn other_legends_keep_the_default_margin() {
+    let top = spec("{}");
+    let hidden = spec(r#"{"position": "right", "show": false}"#);
+    for spec in &[top, hidden] {
+        let series = spec.resolve().unwrap();
+        assert_eq!(spec.margins(&series), (90, 40, 50, 60));
+    }
+}
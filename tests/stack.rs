@@ -0,0 +1,60 @@
+use yew_charts::series::Series;
+use yew_charts::stack::{align, extent, outline, stack, Segment, Stack};
+
+fn tops(layers: &[Vec<Segment>]) -> Vec<Vec<f32>> {
+    layers.iter().map(|l| l.iter().map(|s| s.to).collect()).collect()
+}
+
+#[test]
+fn layers_follow_series_order() {
+    let layers = stack(&[vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0]], Stack::Normal);
+    assert_eq!(tops(&layers), vec![vec![1.0, 2.0], vec![4.0, 6.0], vec![9.0, 6.0]]);
+    assert_eq!(
+        layers[1][0],
+        Segment {
+            value: 3.0,
+            from: 1.0,
+            to: 4.0,
+            total: 9.0
+        }
+    );
+    assert_eq!(layers[2][1].from, layers[2][1].to);
+}
+
+#[test]
+fn negatives_stack_down_from_zero() {
+    let layers = stack(&[vec![2.0], vec![-1.0], vec![3.0], vec![-2.0]], Stack::Normal);
+    let spans: Vec<(f32, f32)> = layers.iter().map(|l| (l[0].from, l[0].to)).collect();
+    assert_eq!(spans, vec![(0.0, 2.0), (0.0, -1.0), (2.0, 5.0), (-1.0, -3.0)]);
+    assert_eq!(layers[0][0].total, 2.0);
+    assert_eq!(extent(&layers), (-3.0, 5.0));
+}
+
+#[test]
+fn percent_normalizes_each_position() {
+    let layers = stack(&[vec![1.0, 0.0], vec![3.0, 0.0]], Stack::Percent);
+    assert_eq!(tops(&layers), vec![vec![25.0, 0.0], vec![100.0, 0.0]]);
+    // the raw values stay available for tooltips
+    assert_eq!(layers[1][0].value, 3.0);
+    assert_eq!(layers[1][0].total, 4.0);
+}
+
+#[test]
+fn mixed_signs_fill_between_bottom_and_top() {
+    // the second series flips from above zero to below it
+    let layers = stack(&[vec![1.0, 1.0], vec![3.0, -1.0]], Stack::Percent);
+    assert_eq!(
+        outline(&[0.0, 10.0], &layers[1]),
+        vec![(0.0, 100.0), (10.0, -50.0), (10.0, 0.0), (0.0, 25.0)]
+    );
+    assert_eq!(extent(&layers), (-50.0, 100.0));
+}
+
+#[test]
+fn align_interpolates_inside_and_zeroes_outside() {
+    let a = Series::new("a", vec![(0, 10), (10, 20)]);
+    let b = Series::new("b", vec![(5, 1), (15, 3)]);
+    let (xs, values) = align(vec![&a, &b]);
+    assert_eq!(xs, vec![0, 5, 10, 15]);
+    assert_eq!(values, vec![vec![10.0, 15.0, 20.0, 0.0], vec![0.0, 1.0, 2.0, 3.0]]);
+}
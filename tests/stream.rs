@@ -0,0 +1,59 @@
+use yew_charts::stream::{Retention, StreamHandle};
+
+fn data(stream: &StreamHandle) -> Vec<(String, Vec<(isize, isize)>)> {
+    stream.snapshot().into_iter().map(|s| (s.name, s.data)).collect()
+}
+
+#[test]
+fn count_keeps_the_latest_samples_of_each_series() {
+    let stream = StreamHandle::new(Retention::Count(2));
+    stream.extend("a", vec![(0, 1), (1, 2), (2, 3)]);
+    stream.push("b", (5, 9));
+    assert_eq!(
+        data(&stream),
+        vec![("a".to_string(), vec![(1, 2), (2, 3)]), ("b".to_string(), vec![(5, 9)])]
+    );
+    // the window starts at the oldest sample kept by any series
+    assert_eq!(stream.x_extent(), Some((1.0, 5.0)));
+    stream.push("b", (6, 0));
+    stream.push("b", (7, 0));
+    assert_eq!(stream.x_extent(), Some((1.0, 7.0)));
+}
+
+#[test]
+fn span_drops_samples_older_than_the_newest_one() {
+    let stream = StreamHandle::new(Retention::Span(10.0));
+    stream.extend("a", vec![(0, 1), (5, 2), (12, 3)]);
+    assert_eq!(data(&stream), vec![("a".to_string(), vec![(5, 2), (12, 3)])]);
+    // a newer sample elsewhere trims this series too
+    stream.push("b", (20, 0));
+    assert_eq!(data(&stream)[0].1, vec![(12, 3)]);
+    assert_eq!(stream.x_extent(), Some((10.0, 20.0)));
+}
+
+#[test]
+fn empty_and_single_point_extents() {
+    let stream = StreamHandle::new(Retention::Count(10));
+    assert_eq!(stream.x_extent(), None);
+    stream.push("a", (3, 1));
+    assert_eq!(stream.x_extent(), Some((3.0, 4.0)));
+    stream.clear();
+    assert_eq!(stream.x_extent(), None);
+}
+
+#[test]
+fn every_change_bumps_the_version() {
+    let stream = StreamHandle::new(Retention::default());
+    let reader = stream.clone();
+    let v0 = reader.version();
+    stream.push("a", (0, 0));
+    let v1 = reader.version();
+    assert!(v1 > v0);
+    stream.extend("a", vec![(1, 1), (2, 2)]);
+    let v2 = reader.version();
+    assert!(v2 > v1);
+    stream.clear();
+    assert!(reader.version() > v2);
+    assert!(reader == stream);
+    assert!(reader != StreamHandle::default());
+}
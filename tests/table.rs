@@ -0,0 +1,100 @@
+use yew_charts::table::{parse_timestamp, Column, Options, Selection, Table, TableError};
+
+fn line_of(err: TableError) -> usize {
+    match err {
+        TableError::Line { line, .. } => line,
+        e => panic!("unexpected error {}", e),
+    }
+}
+
+fn texts(table: &Table, name: &str) -> Vec<String> {
+    let column = table.column(name).unwrap();
+    (0..table.rows()).map(|row| column.text(row)).collect()
+}
+
+#[test]
+fn quoted_fields_keep_delimiters_newlines_and_quotes() {
+    let text = "name,note\n\"Smith, J\",\"said \"\"hi\"\"\"\nLee,\"two\nlines\"\n";
+    let table = Table::parse(text, &Options::default()).unwrap();
+    assert_eq!(table.names, vec!["name", "note"]);
+    assert_eq!(texts(&table, "name"), vec!["Smith, J", "Lee"]);
+    assert_eq!(texts(&table, "note"), vec!["said \"hi\"", "two\nlines"]);
+}
+
+#[test]
+fn crlf_and_blank_lines() {
+    let table = Table::parse("x,y\r\n1,2\r\n\r\n3,4\r\n", &Options::default()).unwrap();
+    assert_eq!(table.points("x", "y").unwrap(), vec![(1, 2), (3, 4)]);
+}
+
+#[test]
+fn header_is_sniffed_from_the_first_row() {
+    let with = Table::parse("time,value\n1,2\n", &Options::default()).unwrap();
+    assert_eq!(with.names, vec!["time", "value"]);
+    assert_eq!(with.rows(), 1);
+    // numbers and dates in the first row make it data
+    let without = Table::parse("1,2\n3,4\n", &Options::default()).unwrap();
+    assert_eq!(without.names, vec!["column1", "column2"]);
+    assert_eq!(without.rows(), 2);
+    let dated = Table::parse("2020-01-01,5\n2020-01-02,6\n", &Options::default()).unwrap();
+    assert_eq!(dated.rows(), 2);
+    assert!(matches!(dated.columns[0], Column::Time(_)));
+    // and an explicit setting wins
+    let forced = Table::parse(
+        "a,b\n1,2\n",
+        &Options {
+            header: Some(false),
+            ..Options::csv()
+        },
+    )
+    .unwrap();
+    assert_eq!(forced.rows(), 2);
+    assert!(!forced.columns[0].is_numeric());
+}
+
+#[test]
+fn delimiter_is_sniffed_from_the_first_line() {
+    let tabs = Table::parse("x\ty\tz,w\n1\t2\t3\n", &Options::default()).unwrap();
+    assert_eq!(tabs.names, vec!["x", "y", "z,w"]);
+    let commas = Table::parse("x,y,z\tw\n1,2,3\n", &Options::default()).unwrap();
+    assert_eq!(commas.names, vec!["x", "y", "z\tw"]);
+    let forced = Table::parse("x,y\t1\n", &Options { header: Some(false), ..Options::tsv() }).unwrap();
+    assert_eq!(texts(&forced, "column1"), vec!["x,y"]);
+}
+
+#[test]
+fn errors_report_the_line_a_record_starts_on() {
+    let parse = |text: &str| Table::parse(text, &Options::csv()).unwrap_err();
+    assert_eq!(line_of(parse("a,b\n1,2\n3\n")), 3);
+    // the quoted newline in the second record moves the third one down
+    assert_eq!(line_of(parse("a,b\n\"x\ny\",2\n\n1,2,3\n")), 5);
+    assert_eq!(line_of(parse("a,b\n1,2\n\"open,3\n4,5\n")), 3);
+    assert_eq!(line_of(parse("a,b\n")), 1);
+    assert!(matches!(parse(""), TableError::Empty));
+    assert!(matches!(parse("\n\n"), TableError::Empty));
+}
+
+#[test]
+fn columns_infer_their_type_from_every_cell() {
+    let text = "t,v,host\n2020-01-01T00:00:00Z,1.5,a\n2020-01-01T00:01:00+01:00,,b\n2020-01-01 00:02,3,4\n";
+    let table = Table::parse(text, &Options::default()).unwrap();
+    assert_eq!(
+        table.columns[0],
+        Column::Time(vec![Some(1577836800.0), Some(1577833260.0), Some(1577836920.0)])
+    );
+    assert_eq!(table.columns[1], Column::Number(vec![Some(1.5), None, Some(3.0)]));
+    assert!(!table.columns[2].is_numeric());
+    assert!(matches!(table.series(&Selection::new("host", "v")), Err(TableError::NotNumeric(_))));
+    assert!(matches!(table.points("t", "nope"), Err(TableError::NoColumn(_))));
+    assert_eq!(parse_timestamp("2021-02-29"), None);
+    assert_eq!(parse_timestamp("2020-02-29"), Some(1582934400.0));
+}
+
+#[test]
+fn series_are_split_and_sorted() {
+    let text = "x,y,host\n3,1,a\n1,2,b\n2,3,a\n,4,a\n";
+    let table = Table::parse(text, &Options::default()).unwrap();
+    let series = table.series(&Selection::new("x", "y").split_by("host")).unwrap();
+    let named: Vec<(&str, &[(isize, isize)])> = series.iter().map(|s| (s.name.as_str(), &s.data[..])).collect();
+    assert_eq!(named, vec![("a", &[(2, 3), (3, 1)][..]), ("b", &[(1, 2)][..])]);
+}
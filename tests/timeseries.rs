@@ -0,0 +1,93 @@
+use yew_charts::series::Series;
+use yew_charts::timeseries::*;
+
+const IRREGULAR: &[(f64, f64)] = &[(0.0, 1.0), (4.0, 3.0), (11.0, 5.0), (12.0, 7.0), (35.0, 2.0)];
+
+#[test]
+fn resample_reduces_each_interval() {
+    let at = |reducer| resample(IRREGULAR, 10.0, 0.0, reducer);
+    assert_eq!(at(Reducer::Mean), vec![(0.0, 2.0), (10.0, 6.0), (30.0, 2.0)]);
+    assert_eq!(at(Reducer::Sum), vec![(0.0, 4.0), (10.0, 12.0), (30.0, 2.0)]);
+    assert_eq!(at(Reducer::Max), vec![(0.0, 3.0), (10.0, 7.0), (30.0, 2.0)]);
+    assert_eq!(at(Reducer::Min), vec![(0.0, 1.0), (10.0, 5.0), (30.0, 2.0)]);
+    assert_eq!(at(Reducer::Last), vec![(0.0, 3.0), (10.0, 7.0), (30.0, 2.0)]);
+}
+
+#[test]
+fn rate_spans_intervals_and_survives_resets() {
+    let counter = [(0.0, 0.0), (5.0, 10.0), (10.0, 20.0), (15.0, 4.0)];
+    // the second interval runs from the sample at 5 to the one at 15: +10, then a reset to 4
+    assert_eq!(
+        resample(&counter, 10.0, 0.0, Reducer::Rate),
+        vec![(0.0, 2.0), (10.0, 1.4)]
+    );
+}
+
+#[test]
+fn resample_ignores_bad_intervals() {
+    assert!(resample(IRREGULAR, 0.0, 0.0, Reducer::Mean).is_empty());
+}
+
+#[test]
+fn rolling_windows_by_count_and_span() {
+    let points = [(0.0, 2.0), (1.0, 4.0), (2.0, 6.0), (10.0, 8.0)];
+    assert_eq!(
+        rolling_mean(&points, Window::Count(2)),
+        vec![(0.0, 2.0), (1.0, 3.0), (2.0, 5.0), (10.0, 7.0)]
+    );
+    assert_eq!(
+        rolling_mean(&points, Window::Span(1.0)),
+        vec![(0.0, 2.0), (1.0, 3.0), (2.0, 5.0), (10.0, 8.0)]
+    );
+    assert_eq!(
+        rolling_percentile(&points, Window::Count(3), 50.0),
+        vec![(0.0, 2.0), (1.0, 3.0), (2.0, 4.0), (10.0, 6.0)]
+    );
+    assert_eq!(rolling_percentile(&points, Window::Count(4), 100.0)[3], (10.0, 8.0));
+}
+
+#[test]
+fn align_puts_series_on_one_grid() {
+    let fast = vec![(0.0, 1.0), (5.0, 1.0), (10.0, 2.0), (20.0, 3.0), (30.0, 4.0)];
+    let slow = vec![(2.0, 10.0), (31.0, 40.0)];
+    let (grid, columns) = align(&[fast, slow.clone()], 10.0, Reducer::Mean, Fill::Gap).unwrap();
+    assert_eq!(grid, vec![0.0, 10.0, 20.0, 30.0]);
+    assert_eq!(columns[0], vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0)]);
+    assert_eq!(columns[1], vec![Some(10.0), None, None, Some(40.0)]);
+
+    let filled = |fill| align(&[slow.clone()], 10.0, Reducer::Mean, fill).unwrap().1.remove(0);
+    assert_eq!(filled(Fill::Zero), vec![Some(10.0), Some(0.0), Some(0.0), Some(40.0)]);
+    assert_eq!(filled(Fill::Previous), vec![Some(10.0), Some(10.0), Some(10.0), Some(40.0)]);
+    assert_eq!(filled(Fill::Linear), vec![Some(10.0), Some(20.0), Some(30.0), Some(40.0)]);
+}
+
+#[test]
+fn series_helpers_keep_name_and_color() {
+    let a = Series::new("a", vec![(0, 1), (15, 3)]).with_color("#FF0000");
+    let b = Series::new("b", vec![(5, 2)]);
+    let aligned = align_series(&[a, b], 10, Reducer::Last, Fill::Gap).unwrap();
+    assert_eq!(aligned[0], Series::new("a", vec![(0, 1), (10, 3)]).with_color("#FF0000"));
+    assert_eq!(aligned[1], Series::new("b", vec![(0, 2)]));
+}
+
+#[test]
+fn non_finite_x_is_dropped() {
+    let points = [(f64::NAN, 1.0), (3.0, 2.0), (f64::INFINITY, 5.0), (12.0, 4.0)];
+    assert_eq!(resample(&points, 10.0, 0.0, Reducer::Sum), vec![(0.0, 2.0), (10.0, 4.0)]);
+    let (grid, columns) = align(&[points.to_vec()], 10.0, Reducer::Sum, Fill::Gap).unwrap();
+    assert_eq!(grid, vec![0.0, 10.0]);
+    assert_eq!(columns[0], vec![Some(2.0), Some(4.0)]);
+}
+
+#[test]
+fn align_refuses_huge_grids() {
+    let wide = vec![(0.0, 1.0), (1e12, 2.0)];
+    assert_eq!(
+        align(&[wide], 1.0, Reducer::Mean, Fill::Gap),
+        Err(AlignError::TooManySteps { steps: 1e12 + 1.0 })
+    );
+    let series = [Series::new("a", vec![(0, 1), (10_000_000, 2)])];
+    assert!(align_series(&series, 1, Reducer::Mean, Fill::Gap).is_err());
+    let edge = vec![(0.0, 1.0), ((MAX_STEPS - 1) as f64, 2.0)];
+    assert_eq!(align(&[edge], 1.0, Reducer::Mean, Fill::Gap).unwrap().0.len(), MAX_STEPS);
+}
@@ -0,0 +1,182 @@
+use serde_json::{json, Value};
+use yew_charts::spec::Row;
+use yew_charts::transform::{apply, Transform, TransformError};
+
+fn rows(value: Value) -> Vec<Row> {
+    serde_json::from_value(value).unwrap()
+}
+
+fn transforms(value: Value) -> Vec<Transform> {
+    serde_json::from_value(value).unwrap()
+}
+
+fn requests() -> Vec<Row> {
+    rows(json!([
+        {"t": 5, "host": "a", "status": 200, "ms": 10},
+        {"t": 62, "host": "b", "status": 500, "ms": 40},
+        {"t": 64, "host": "a", "status": 503, "ms": 30},
+        {"t": 70, "host": "a", "status": 200, "ms": null},
+        {"t": 130, "host": "b", "status": 500, "ms": 20}
+    ]))
+}
+
+#[test]
+fn filter_compares_numbers_and_combines_predicates() {
+    let out = apply(
+        requests(),
+        &transforms(json!([
+            {"filter": {"and": [
+                {"field": "status", "op": "ge", "value": 500},
+                {"not": {"field": "host", "op": "eq", "value": "b"}}
+            ]}}
+        ])),
+    )
+    .unwrap();
+    assert_eq!(out, rows(json!([{"t": 64, "host": "a", "status": 503, "ms": 30}])));
+}
+
+#[test]
+fn filter_in_and_valid() {
+    let t = transforms(json!([
+        {"filter": {"field": "status", "op": "in", "value": [200, 503]}},
+        {"filter": {"field": "ms", "op": "valid"}}
+    ]));
+    let out = apply(requests(), &t).unwrap();
+    let ts: Vec<&Value> = out.iter().map(|r| &r["t"]).collect();
+    assert_eq!(ts, vec![&json!(5), &json!(64)]);
+}
+
+#[test]
+fn ordering_ops_never_match_mixed_types() {
+    let t = transforms(json!([{"filter": {"field": "host", "op": "lt", "value": 10}}]));
+    assert!(apply(requests(), &t).unwrap().is_empty());
+}
+
+#[test]
+fn aggregate_groups_in_first_seen_order() {
+    let t = transforms(json!([
+        {"aggregate": {"groupby": ["host"], "fields": [
+            {"op": "count", "as": "n"},
+            {"op": "valid", "field": "ms", "as": "timed"},
+            {"op": "sum", "field": "ms", "as": "total"},
+            {"op": "mean", "field": "ms", "as": "avg"},
+            {"op": "median", "field": "status", "as": "median"},
+            {"op": "max", "field": "t", "as": "last"},
+            {"op": "distinct", "field": "status", "as": "codes"}
+        ]}}
+    ]));
+    let out = apply(requests(), &t).unwrap();
+    assert_eq!(
+        out,
+        rows(json!([
+            {"host": "a", "n": 3, "timed": 2, "total": 40.0, "avg": 20.0, "median": 200.0, "last": 70.0, "codes": 2},
+            {"host": "b", "n": 2, "timed": 2, "total": 60.0, "avg": 30.0, "median": 500.0, "last": 130.0, "codes": 1}
+        ]))
+    );
+}
+
+#[test]
+fn aggregate_rejects_text() {
+    let t = transforms(json!([{"aggregate": {"fields": [{"op": "sum", "field": "host", "as": "x"}]}}]));
+    assert_eq!(
+        apply(requests(), &t),
+        Err(TransformError::NotNumeric {
+            row: 0,
+            field: "host".to_string()
+        })
+    );
+}
+
+#[test]
+fn aggregate_needs_a_field() {
+    let t = transforms(json!([{"aggregate": {"fields": [{"op": "mean", "as": "x"}]}}]));
+    assert_eq!(
+        apply(requests(), &t),
+        Err(TransformError::MissingField { op: "aggregate" })
+    );
+}
+
+#[test]
+fn bin_then_count_per_bucket() {
+    let t = transforms(json!([
+        {"bin": {"field": "t", "step": 60, "as": "minute"}},
+        {"aggregate": {"groupby": ["minute"], "fields": [{"op": "count", "as": "n"}]}}
+    ]));
+    let out = apply(requests(), &t).unwrap();
+    assert_eq!(
+        out,
+        rows(json!([
+            {"minute": 0.0, "n": 1},
+            {"minute": 60.0, "n": 3},
+            {"minute": 120.0, "n": 1}
+        ]))
+    );
+}
+
+#[test]
+fn bin_honours_anchor_and_rejects_bad_steps() {
+    let t = transforms(json!([{"bin": {"field": "t", "step": 60, "anchor": 30, "as": "b"}}]));
+    let out = apply(requests(), &t).unwrap();
+    assert_eq!(out[0]["b"], json!(-30.0));
+    assert_eq!(out[1]["b"], json!(30.0));
+
+    let t = transforms(json!([{"bin": {"field": "t", "step": 0, "as": "b"}}]));
+    assert_eq!(apply(requests(), &t), Err(TransformError::BadStep(0.0)));
+}
+
+#[test]
+fn sort_by_several_fields_then_limit() {
+    let t = transforms(json!([
+        {"sort": [{"field": "host"}, {"field": "t", "order": "descending"}]},
+        {"limit": 3}
+    ]));
+    let out = apply(requests(), &t).unwrap();
+    let ts: Vec<&Value> = out.iter().map(|r| &r["t"]).collect();
+    assert_eq!(ts, vec![&json!(70), &json!(64), &json!(5)]);
+}
+
+#[test]
+fn sort_puts_nulls_first() {
+    let t = transforms(json!([{"sort": [{"field": "ms"}]}]));
+    let out = apply(requests(), &t).unwrap();
+    assert_eq!(out[0]["ms"], Value::Null);
+    assert_eq!(out[1]["ms"], json!(10));
+}
+
+#[test]
+fn calculate_derives_fields() {
+    let t = transforms(json!([
+        {"calculate": {"op": "div", "left": {"field": "ms"}, "right": 1000, "as": "s"}},
+        {"calculate": {"op": "div", "left": 1, "right": {"field": "zero"}, "as": "inf"}}
+    ]));
+    let input = rows(json!([{"ms": 250, "zero": 0}, {"ms": null, "zero": 0}]));
+    let out = apply(input, &t).unwrap();
+    assert_eq!(out[0]["s"], json!(0.25));
+    // no json number for infinity
+    assert_eq!(out[0]["inf"], Value::Null);
+    assert_eq!(out[1]["s"], Value::Null);
+}
+
+#[test]
+fn empty_pipeline_is_identity() {
+    assert_eq!(apply(requests(), &[]).unwrap(), requests());
+}
+
+#[test]
+fn errors_name_the_input_row() {
+    // the filter drops rows 0 and 3, the failing row is still the fifth
+    let mut input = requests();
+    input[4].insert("ms".to_string(), json!("slow"));
+    let t = transforms(json!([
+        {"filter": {"field": "status", "op": "ge", "value": 500}},
+        {"sort": [{"field": "t", "order": "descending"}]},
+        {"calculate": {"op": "div", "left": {"field": "ms"}, "right": 1000, "as": "s"}}
+    ]));
+    assert_eq!(
+        apply(input, &t),
+        Err(TransformError::NotNumeric {
+            row: 4,
+            field: "ms".to_string()
+        })
+    );
+}
@@ -0,0 +1,47 @@
+use yew_charts::series::Series;
+use yew_charts::uncertainty::{band_series, error_bar_series, Estimate, Spread};
+
+#[test]
+fn mean_and_standard_deviations() {
+    let e = Estimate::new(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, f64::NAN], Spread::StdDev(2.0)).unwrap();
+    assert_eq!(e.center, 5.0);
+    // sample standard deviation of the above is sqrt(32 / 7)
+    let sd = (32.0f64 / 7.0).sqrt();
+    assert!((e.lower - (5.0 - 2.0 * sd)).abs() < 1e-12);
+    assert!((e.upper - (5.0 + 2.0 * sd)).abs() < 1e-12);
+}
+
+#[test]
+fn median_between_percentiles() {
+    let samples: Vec<f64> = (0..=100).map(f64::from).collect();
+    let e = Estimate::new(&samples, Spread::Percentiles(95.0, 5.0)).unwrap();
+    assert_eq!((e.lower, e.center, e.upper), (5.0, 50.0, 95.0));
+    assert!(Estimate::new(&[], Spread::StdDev(1.0)).is_none());
+}
+
+#[test]
+fn bands_widen_the_fitted_domain() {
+    let groups = vec![(0, vec![10.0, 20.0, 30.0]), (1, vec![]), (2, vec![40.0])];
+    let s = band_series("latency", &groups, Spread::Percentiles(0.0, 100.0), 1.0);
+    assert_eq!(s.data, vec![(0, 20), (2, 40)]);
+    assert_eq!(s.bounds, vec![(10, 30), (40, 40)]);
+    let d = Series::extent(&[s]).unwrap();
+    assert_eq!(d.y, (10.0, 40.0));
+}
+
+#[test]
+fn scale_keeps_sub_unit_bands() {
+    let groups = vec![(0, vec![0.010, 0.012, 0.020])];
+    let s = band_series("latency", &groups, Spread::Percentiles(0.0, 100.0), 1.0);
+    assert_eq!((s.data, s.bounds), (vec![(0, 0)], vec![(0, 0)]));
+    let s = band_series("latency", &groups, Spread::Percentiles(0.0, 100.0), 1000.0);
+    assert_eq!((s.data, s.bounds), (vec![(0, 12)], vec![(10, 20)]));
+}
+
+#[test]
+fn empty_categories_get_no_bar() {
+    let bars = error_bar_series("p50", &[vec![1.0, 3.0], vec![]], Spread::StdDev(1.0));
+    assert_eq!(bars.values[0], 2.0);
+    assert!(bars.values[1].is_nan());
+    assert_eq!(bars.bounds.len(), 2);
+}
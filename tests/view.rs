@@ -0,0 +1,66 @@
+use yew_charts::view::{client_in_rect, Domain, Projection};
+
+#[test]
+fn zooming_keeps_the_anchor_in_place() {
+    let base = Domain::new((0.0, 100.0), (0.0, 10.0));
+    let zoomed = base.zoom_at(&base, (25.0, 5.0), (0.5, 1.0), (1.0, 10.0));
+    assert_eq!(zoomed, Domain::new((12.5, 62.5), (0.0, 10.0)));
+    // the anchor sits at the same fraction of the span before and after
+    assert_eq!((25.0 - zoomed.x.0) / zoomed.width(), 0.25);
+}
+
+#[test]
+fn zooming_stops_at_the_limits() {
+    let base = Domain::new((0.0, 100.0), (0.0, 10.0));
+    let deep = base.zoom_at(&base, (50.0, 5.0), (0.01, 0.01), (1.0, 4.0));
+    assert_eq!(deep, Domain::new((37.5, 62.5), (3.75, 6.25)));
+    let out = base.zoom_at(&base, (50.0, 5.0), (3.0, 3.0), (0.5, 4.0));
+    assert_eq!(out, Domain::new((-50.0, 150.0), (-5.0, 15.0)));
+    // an empty axis can't be scaled
+    let flat = Domain::new((1.0, 1.0), (0.0, 10.0));
+    assert_eq!(flat.zoom_at(&base, (1.0, 5.0), (0.5, 1.0), (1.0, 10.0)).x, (1.0, 1.0));
+}
+
+#[test]
+fn panning_shifts_both_axes() {
+    let d = Domain::new((0.0, 10.0), (-1.0, 1.0)).pan((5.0, -1.0));
+    assert_eq!(d, Domain::new((5.0, 15.0), (-2.0, 0.0)));
+}
+
+#[test]
+fn projections_round_trip() {
+    let proj = Projection::from_pixel_range((60..460, 90..290), Domain::new((0.0, 100.0), (-10.0, 10.0)));
+    assert_eq!((proj.left, proj.top, proj.width, proj.height), (60.0, 90.0, 400.0, 200.0));
+    // y grows upwards in data and downwards on screen
+    assert_eq!(proj.to_pixel((0.0, 10.0)), (60.0, 90.0));
+    assert_eq!(proj.to_pixel((100.0, -10.0)), (460.0, 290.0));
+    assert_eq!(proj.to_data(proj.to_pixel((25.0, 5.0))), (25.0, 5.0));
+    assert_eq!(proj.delta_to_data((40.0, 20.0)), (10.0, -2.0));
+    assert!(proj.contains((60.0, 290.0)));
+    assert!(!proj.contains((59.0, 100.0)));
+}
+
+#[test]
+fn client_positions_stretch_over_the_view_box() {
+    let rect = (100.0, 50.0, 400.0, 200.0);
+    let view_box = (0.0, 0.0, 800.0, 600.0);
+    assert_eq!(client_in_rect(rect, view_box, (100, 50)), Some((0.0, 0.0)));
+    assert_eq!(client_in_rect(rect, view_box, (300, 150)), Some((400.0, 300.0)));
+    assert_eq!(client_in_rect(rect, (-20.0, 10.0, 800.0, 600.0), (500, 250)), Some((780.0, 610.0)));
+    // not laid out yet
+    assert_eq!(client_in_rect((0.0, 0.0, 0.0, 0.0), view_box, (1, 1)), None);
+}
+
+#[test]
+fn visible_points_reach_past_the_edges() {
+    let xs = [0.0, 10.0, 20.0, 30.0, 40.0];
+    let visible = |lo: f32, hi: f32| Domain::new((lo, hi), (0.0, 1.0)).visible(&xs, |x| *x);
+    assert_eq!(visible(15.0, 25.0), 1..4);
+    assert_eq!(visible(10.0, 20.0), 0..4);
+    assert_eq!(visible(-5.0, 50.0), 0..5);
+    // a segment crossing the whole domain keeps both its ends
+    assert_eq!(visible(12.0, 18.0), 1..3);
+    assert_eq!(visible(50.0, 60.0), 4..5);
+    assert_eq!(visible(-20.0, -10.0), 0..1);
+    assert_eq!(Domain::new((0.0, 1.0), (0.0, 1.0)).visible(&[] as &[f32], |x| *x), 0..0);
+}
@@ -11,3 +11,164 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+mod brushing {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::*;
+    use web_sys::{Element, MouseEvent, MouseEventInit};
+    use yew::{App, Callback};
+    use yew_charts::brush::{BrushMode, BrushSelection};
+    use yew_charts::chart::Props;
+    use yew_charts::ChartComponent;
+
+    // the builder setters are deprecated in newer web-sys but are all 0.3.44 has
+    #[allow(deprecated)]
+    fn mouse(svg: &Element, kind: &str, (x, y): (f64, f64)) {
+        let rect = svg.get_bounding_client_rect();
+        let mut init = MouseEventInit::new();
+        init.client_x((rect.left() + x) as i32).client_y((rect.top() + y) as i32);
+        let event = MouseEvent::new_with_mouse_event_init_dict(kind, &init).unwrap();
+        svg.dispatch_event(&event).unwrap();
+    }
+
+    fn brush(sync_group: Option<String>) -> Option<BrushSelection> {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let host = document.create_element("div").unwrap();
+        host.set_attribute("style", "width: 600px; height: 400px").unwrap();
+        document.query_selector("body").unwrap().unwrap().append_child(&host).unwrap();
+        let selection = Rc::new(RefCell::new(None));
+        let seen = selection.clone();
+        let props = Props {
+            width: 600,
+            height: 400,
+            data: vec![(0, 0), (100, 50), (200, 100)],
+            x_domain: (0.0, 200.0),
+            y_domain: (0.0, 100.0),
+            min_zoom: 1.0,
+            max_zoom: 100.0,
+            max_fps: 30,
+            brush: Some(BrushMode::X),
+            on_brush: Some(Callback::from(move |s| *seen.borrow_mut() = Some(s))),
+            sync_group,
+            ..Props::default()
+        };
+        App::<ChartComponent>::new().mount_with_props(host.clone(), props);
+        let svg = host.query_selector("svg").unwrap().unwrap().dyn_into::<Element>().unwrap();
+        // across the middle of the plot area, which spans 60..540 horizontally
+        mouse(&svg, "mousedown", (180.0, 200.0));
+        mouse(&svg, "mousemove", (300.0, 200.0));
+        mouse(&svg, "mousemove", (420.0, 200.0));
+        mouse(&svg, "mouseup", (420.0, 200.0));
+        host.remove();
+        selection.take()
+    }
+
+    #[wasm_bindgen_test]
+    fn brushing_selects_a_range() {
+        let selection = brush(None).expect("no selection");
+        assert_eq!(selection.domain.x, (50.0, 150.0));
+        assert_eq!(selection.points, vec![(0, 1)]);
+    }
+
+    #[wasm_bindgen_test]
+    fn brushing_works_on_synced_charts() {
+        // moving the synced crosshair must not swallow the drag
+        let selection = brush(Some("brushing".to_string())).expect("no selection");
+        assert_eq!(selection.domain.x, (50.0, 150.0));
+    }
+}
+
+mod reconnecting {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+    use yew::html::Children;
+    use yew::{App, Callback};
+    use yew_charts::source::{
+        Connection, ConnectionStatus, Format, Props, SourceComponent, SourceKind, Transport, TransportEvent,
+    };
+    use yew_charts::stream::StreamHandle;
+
+    struct Open;
+
+    impl Connection for Open {}
+
+    /// Opens every connection, keeping its events for the test to send.
+    #[derive(Default)]
+    struct MockTransport {
+        opened: RefCell<Vec<Callback<TransportEvent>>>,
+    }
+
+    impl MockTransport {
+        fn send(&self, event: TransportEvent) {
+            let latest = self.opened.borrow().last().cloned().expect("nothing opened");
+            latest.emit(event);
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn open(&self, _url: &str, events: Callback<TransportEvent>) -> Result<Box<dyn Connection>, String> {
+            self.opened.borrow_mut().push(events);
+            Ok(Box::new(Open))
+        }
+    }
+
+    async fn sleep(ms: i32) {
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+                .unwrap();
+        });
+        JsFuture::from(promise).await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn backs_off_until_connected_then_starts_over() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let host = document.create_element("div").unwrap();
+        document.query_selector("body").unwrap().unwrap().append_child(&host).unwrap();
+        let transport = Rc::new(MockTransport::default());
+        let statuses = Rc::new(RefCell::new(Vec::new()));
+        let seen = statuses.clone();
+        let stream = StreamHandle::default();
+        let props = Props {
+            url: "mock://metrics".to_string(),
+            stream: stream.clone(),
+            kind: SourceKind::WebSocket,
+            format: Format::Json,
+            transport: Some(transport.clone()),
+            backoff: Duration::from_millis(5),
+            max_backoff: Duration::from_millis(10),
+            on_status: Some(Callback::from(move |s| seen.borrow_mut().push(s))),
+            children: Children::default(),
+        };
+        App::<SourceComponent>::new().mount_with_props(host.clone(), props);
+        for _ in 0..3 {
+            transport.send(TransportEvent::Closed);
+            // an error right after the close is the same failure
+            transport.send(TransportEvent::Error("reset".to_string()));
+            sleep(40).await;
+        }
+        assert_eq!(transport.opened.borrow().len(), 4);
+        transport.send(TransportEvent::Opened);
+        transport.send(TransportEvent::Message(r#"{"series": "a", "x": 1, "y": 2}"#.to_string()));
+        transport.send(TransportEvent::Closed);
+        host.remove();
+        let attempts: Vec<Option<u32>> = statuses
+            .borrow()
+            .iter()
+            .filter(|s| **s != ConnectionStatus::Connecting)
+            .map(|s| match s {
+                ConnectionStatus::Reconnecting { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![Some(1), Some(2), Some(3), None, Some(1)]);
+        assert_eq!(stream.snapshot()[0].data, vec![(1, 2)]);
+    }
+}